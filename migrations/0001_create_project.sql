@@ -0,0 +1,17 @@
+-- IF NOT EXISTS: databases created before migrations were introduced already have this table
+CREATE TABLE IF NOT EXISTS project(
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    creator TEXT NOT NULL,
+    asset_price TEXT NOT NULL,
+    token_name TEXT NOT NULL,
+    share_count TEXT NOT NULL,
+    investors_share TEXT NOT NULL,
+    share_id TEXT NOT NULL,
+    app_id TEXT NOT NULL,
+    invest_b TEXT NOT NULL,
+    staking_b TEXT NOT NULL,
+    central_b TEXT NOT NULL,
+    customer_b TEXT NOT NULL,
+    uuid TEXT NOT NULL
+);
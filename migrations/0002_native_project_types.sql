@@ -0,0 +1,28 @@
+-- Moves the project columns from TEXT to native types, converting the existing rows in place.
+-- Escrow programs were stored base64 encoded.
+
+ALTER TABLE project RENAME COLUMN share_id TO shares_asset_id;
+ALTER TABLE project RENAME COLUMN app_id TO central_app_id;
+ALTER TABLE project RENAME COLUMN invest_b TO invest_program;
+ALTER TABLE project RENAME COLUMN staking_b TO staking_program;
+ALTER TABLE project RENAME COLUMN central_b TO central_program;
+ALTER TABLE project RENAME COLUMN customer_b TO customer_program;
+
+-- amounts are u64 on chain, which doesn't fit in BIGINT
+ALTER TABLE project
+    ALTER COLUMN asset_price TYPE NUMERIC(20, 0) USING asset_price::NUMERIC(20, 0),
+    ALTER COLUMN share_count TYPE NUMERIC(20, 0) USING share_count::NUMERIC(20, 0),
+    ALTER COLUMN investors_share TYPE SMALLINT USING investors_share::SMALLINT,
+    ALTER COLUMN shares_asset_id TYPE BIGINT USING shares_asset_id::BIGINT,
+    ALTER COLUMN central_app_id TYPE BIGINT USING central_app_id::BIGINT,
+    ALTER COLUMN invest_program TYPE BYTEA USING decode(invest_program, 'base64'),
+    ALTER COLUMN staking_program TYPE BYTEA USING decode(staking_program, 'base64'),
+    ALTER COLUMN central_program TYPE BYTEA USING decode(central_program, 'base64'),
+    ALTER COLUMN customer_program TYPE BYTEA USING decode(customer_program, 'base64'),
+    ALTER COLUMN uuid TYPE UUID USING uuid::UUID;
+
+ALTER TABLE project
+    ADD CONSTRAINT project_amounts_check CHECK (asset_price >= 0 AND share_count >= 0),
+    ADD CONSTRAINT project_investors_share_check CHECK (investors_share BETWEEN 0 AND 100);
+
+CREATE UNIQUE INDEX project_uuid_idx ON project (uuid);
@@ -0,0 +1,19 @@
+-- Nonces handed out to be signed by a wallet. Deleted when used.
+CREATE TABLE auth_challenge(
+    nonce TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX auth_challenge_expires_at_idx ON auth_challenge (expires_at);
+
+-- Only the hash of the tokens is stored
+CREATE TABLE session(
+    token_hash TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX session_expires_at_idx ON session (expires_at);
@@ -0,0 +1,6 @@
+-- Projects can be renamed and archived (soft deleted) by their creator.
+-- Archived projects are kept, but not returned anymore.
+
+ALTER TABLE project
+    ADD COLUMN updated_at TIMESTAMPTZ,
+    ADD COLUMN archived_at TIMESTAMPTZ;
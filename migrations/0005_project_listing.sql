@@ -0,0 +1,10 @@
+-- Creation time, to sort the listed projects. Existing projects get the time of the migration.
+-- Listing is paginated with keyset (sort column, id) cursors, so the indexes include the id.
+
+ALTER TABLE project ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE INDEX project_created_at_idx ON project (created_at, id) WHERE archived_at IS NULL;
+CREATE INDEX project_name_idx ON project (name, id) WHERE archived_at IS NULL;
+CREATE INDEX project_asset_price_idx ON project (asset_price, id) WHERE archived_at IS NULL;
+CREATE INDEX project_creator_idx ON project (creator);
+CREATE INDEX project_token_name_idx ON project (token_name);
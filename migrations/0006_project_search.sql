@@ -0,0 +1,8 @@
+-- Full-text search. 'simple' configuration: names aren't necessarily english, and shouldn't be stemmed.
+
+ALTER TABLE project ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', name), 'A') ||
+    setweight(to_tsvector('simple', token_name), 'B')
+) STORED;
+
+CREATE INDEX project_search_idx ON project USING GIN (search_vector);
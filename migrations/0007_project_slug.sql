@@ -0,0 +1,20 @@
+-- Human readable identifiers for the project links. project.slug is the current one,
+-- project_slug contains all the slugs a project ever had, so links with old slugs can be redirected.
+
+ALTER TABLE project ADD COLUMN slug TEXT;
+
+-- existing projects: the name, with the start of the uuid, to not have to resolve collisions here
+UPDATE project SET slug = trim(both '-' from
+    lower(regexp_replace(name || '-' || left(uuid::TEXT, 8), '[^a-zA-Z0-9]+', '-', 'g')));
+
+ALTER TABLE project ALTER COLUMN slug SET NOT NULL;
+CREATE UNIQUE INDEX project_slug_idx ON project (slug);
+
+CREATE TABLE project_slug(
+    slug TEXT PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX project_slug_project_id_idx ON project_slug (project_id);
+
+INSERT INTO project_slug (slug, project_id) SELECT slug, id FROM project;
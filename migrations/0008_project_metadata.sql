@@ -0,0 +1,55 @@
+-- Off-chain project information, editable by the creator. At most one row per project.
+-- The description is markdown, rendered (and sanitized) when returned.
+
+CREATE TABLE project_metadata(
+    project_id INTEGER PRIMARY KEY REFERENCES project (id),
+    description TEXT NOT NULL DEFAULT '',
+    logo_url TEXT,
+    cover_url TEXT,
+    website_url TEXT,
+    social_links TEXT[] NOT NULL DEFAULT '{}',
+    category TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- The search matches the name, token name and description together, so they're indexed as one vector.
+-- Maintained by triggers: a generated column can't read project_metadata.
+
+ALTER TABLE project ADD COLUMN full_search_vector TSVECTOR NOT NULL DEFAULT '';
+
+UPDATE project SET full_search_vector = search_vector;
+
+CREATE INDEX project_full_search_idx ON project USING GIN (full_search_vector);
+
+-- replaced by project_full_search_idx
+DROP INDEX project_search_idx;
+
+CREATE FUNCTION refresh_full_search_vector(p_project_id INTEGER) RETURNS VOID AS $$
+    UPDATE project SET full_search_vector = search_vector || coalesce(
+        (SELECT setweight(to_tsvector('simple', description), 'C') FROM project_metadata WHERE project_id = p_project_id),
+        ''
+    )
+    WHERE id = p_project_id;
+$$ LANGUAGE SQL;
+
+CREATE FUNCTION project_full_search_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_full_search_vector(NEW.id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION project_metadata_full_search_trigger() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM refresh_full_search_vector(NEW.project_id);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- AFTER: search_vector is generated after the BEFORE triggers.
+-- Doesn't fire again for its own update (only full_search_vector is set).
+CREATE TRIGGER project_full_search AFTER INSERT OR UPDATE OF name, token_name ON project
+    FOR EACH ROW EXECUTE FUNCTION project_full_search_trigger();
+
+CREATE TRIGGER project_metadata_full_search AFTER INSERT OR UPDATE OF description ON project_metadata
+    FOR EACH ROW EXECUTE FUNCTION project_metadata_full_search_trigger();
@@ -0,0 +1,15 @@
+-- Uploaded images. The content (original and thumbnail) is in the blob store.
+
+CREATE TABLE image(
+    id UUID PRIMARY KEY,
+    uploader TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE project_metadata
+    ADD COLUMN logo_image_id UUID REFERENCES image (id),
+    ADD COLUMN cover_image_id UUID REFERENCES image (id);
@@ -0,0 +1,27 @@
+-- Investments, indexed from the chain (see indexing::investments).
+-- Amounts are NUMERIC, as they're u64.
+
+CREATE TABLE investments(
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    -- the shares transfer from the invest escrow
+    txid TEXT NOT NULL UNIQUE,
+    investor TEXT NOT NULL,
+    shares NUMERIC NOT NULL,
+    -- microalgos
+    paid NUMERIC NOT NULL,
+    round BIGINT NOT NULL,
+    round_time TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX investments_project_id_round_idx ON investments (project_id, round);
+
+-- The last round processed by each indexer (kind) for each project, to resume after a restart.
+
+CREATE TABLE indexer_cursor(
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    kind TEXT NOT NULL,
+    last_round BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (project_id, kind)
+);
@@ -0,0 +1,15 @@
+-- Payments to the customer escrows (project revenue), indexed from the chain (see indexing::customer_payments).
+
+CREATE TABLE customer_payments(
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    txid TEXT NOT NULL UNIQUE,
+    payer TEXT NOT NULL,
+    -- microalgos
+    amount NUMERIC NOT NULL,
+    note BYTEA,
+    round BIGINT NOT NULL,
+    round_time TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX customer_payments_project_id_round_time_idx ON customer_payments (project_id, round_time);
@@ -0,0 +1,27 @@
+-- Withdrawals from the central escrow to the creator, indexed from the chain (see indexing::withdrawals).
+
+CREATE TABLE withdrawals(
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    txid TEXT NOT NULL UNIQUE,
+    receiver TEXT NOT NULL,
+    -- microalgos
+    amount NUMERIC NOT NULL,
+    round BIGINT NOT NULL,
+    round_time TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX withdrawals_project_id_round_idx ON withdrawals (project_id, round);
+
+-- Justifications written by the creator. Not a foreign key to withdrawals:
+-- they can be written before the withdrawal is indexed (pending).
+-- Keyed by project: the txid isn't checked against the chain, describing it doesn't affect other projects.
+
+CREATE TABLE withdrawal_descriptions(
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    txid TEXT NOT NULL,
+    description TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (project_id, txid)
+);
@@ -0,0 +1,17 @@
+-- Shares staked and unstaked, and dividends harvested by the investors, indexed from the chain (see indexing::staking).
+
+CREATE TABLE staking_events(
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    txid TEXT NOT NULL UNIQUE,
+    investor TEXT NOT NULL,
+    -- stake, unstake or harvest
+    kind TEXT NOT NULL,
+    -- shares, or microalgos for harvests
+    amount NUMERIC NOT NULL,
+    round BIGINT NOT NULL,
+    round_time TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX staking_events_project_id_investor_idx ON staking_events (project_id, investor);
+CREATE INDEX investments_project_id_investor_idx ON investments (project_id, investor);
@@ -0,0 +1,14 @@
+-- Current holders of the projects' shares, refreshed by the indexer (see indexing::share_holders).
+-- Free shares are in the holder's account, staked ones in the staking escrow.
+
+CREATE TABLE share_holders(
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    address TEXT NOT NULL,
+    free_shares NUMERIC NOT NULL DEFAULT 0,
+    staked_shares NUMERIC NOT NULL DEFAULT 0,
+    total_shares NUMERIC GENERATED ALWAYS AS (free_shares + staked_shares) STORED,
+    PRIMARY KEY (project_id, address)
+);
+
+-- the listing's order
+CREATE INDEX share_holders_project_id_total_shares_idx ON share_holders (project_id, total_shares DESC, address);
@@ -0,0 +1,11 @@
+-- Funding statistics, computed from the indexed investments and refreshed by the indexer.
+
+CREATE TABLE project_stats(
+    project_id INTEGER PRIMARY KEY REFERENCES project (id),
+    shares_sold NUMERIC NOT NULL,
+    investor_count INTEGER NOT NULL,
+    first_investment_at TIMESTAMPTZ,
+    last_investment_at TIMESTAMPTZ,
+    shares_sold_last_7_days NUMERIC NOT NULL,
+    refreshed_at TIMESTAMPTZ NOT NULL
+);
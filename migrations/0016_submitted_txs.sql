@@ -0,0 +1,17 @@
+-- Transactions relayed to algod (POST /tx/submit), polled until confirmed or expired (see relay).
+
+CREATE TABLE submitted_txs(
+    txid TEXT PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES project (id),
+    -- base64, null for transactions submitted alone
+    group_id TEXT,
+    -- pending, confirmed, rejected or expired
+    status TEXT NOT NULL,
+    last_valid BIGINT NOT NULL,
+    confirmed_round BIGINT,
+    error TEXT,
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX submitted_txs_pending_idx ON submitted_txs (submitted_at) WHERE status = 'pending';
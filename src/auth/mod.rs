@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+pub mod session;
+pub mod signature;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    /// How long the user has to sign the challenge
+    pub challenge_ttl_secs: i64,
+    pub session_ttl_secs: i64,
+}
@@ -0,0 +1,233 @@
+use algonaut::core::Address;
+use chrono::{DateTime, Duration, Utc};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{dao::auth_dao::AuthDao, error::ServiceError};
+
+use super::{signature::verify_signature, AuthConfig};
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequestJson {
+    pub address: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChallengeJson {
+    pub nonce: String,
+    /// What has to be signed (as bytes) with the wallet
+    pub message: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyChallengeJson {
+    pub address: String,
+    pub nonce: String,
+    /// base64
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionJson {
+    /// To be sent in the Authorization header: "Bearer <token>"
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub async fn create_challenge(
+    dao: &dyn AuthDao,
+    config: &AuthConfig,
+    request: &ChallengeRequestJson,
+) -> Result<ChallengeJson, ServiceError> {
+    let address = parse_address(&request.address)?;
+    let nonce = random_token();
+    let expires_at = Utc::now() + Duration::seconds(config.challenge_ttl_secs);
+
+    dao.save_challenge(&nonce, &address, &expires_at).await?;
+
+    Ok(ChallengeJson {
+        message: challenge_message(&address, &nonce),
+        nonce,
+        expires_at,
+    })
+}
+
+/// Verifies the signed challenge and starts a session for the address.
+pub async fn verify_challenge(
+    dao: &dyn AuthDao,
+    config: &AuthConfig,
+    request: &VerifyChallengeJson,
+) -> Result<SessionJson, ServiceError> {
+    let address = parse_address(&request.address)?;
+
+    // taken before verifying the signature: a nonce gets a single attempt
+    if !dao.take_challenge(&request.nonce, &address).await? {
+        return Err(ServiceError::Unauthorized(
+            "Challenge not found or expired".to_owned(),
+        ));
+    }
+    verify_signature(
+        &address,
+        challenge_message(&address, &request.nonce).as_bytes(),
+        &request.signature,
+    )?;
+
+    let token = random_token();
+    let expires_at = Utc::now() + Duration::seconds(config.session_ttl_secs);
+    dao.save_session(&hash_token(&token), &address, &expires_at)
+        .await?;
+
+    log::debug!("Started session for: {}", address);
+
+    Ok(SessionJson { token, expires_at })
+}
+
+/// Returns the address of the session with the token
+pub async fn authenticate(dao: &dyn AuthDao, token: &str) -> Result<Address, ServiceError> {
+    dao.load_session(&hash_token(token))
+        .await?
+        .ok_or_else(|| ServiceError::Unauthorized("Session not found or expired".to_owned()))
+}
+
+fn challenge_message(address: &Address, nonce: &str) -> String {
+    format!("Sign in to Capi\naddress: {}\nnonce: {}", address, nonce)
+}
+
+fn parse_address(address: &str) -> Result<Address, ServiceError> {
+    address
+        .parse()
+        .map_err(|_| ServiceError::InvalidInput(format!("Invalid address: {}", address)))
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+fn hash_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::{
+        authenticate, create_challenge, verify_challenge, ChallengeRequestJson, VerifyChallengeJson,
+    };
+    use crate::{auth::AuthConfig, dao::auth_dao::AuthDao, error::ServiceError};
+    use algonaut::core::Address;
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use data_encoding::BASE64;
+    use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+    use tokio::test;
+
+    #[test]
+    async fn test_signed_challenge_starts_session_once() -> Result<()> {
+        let dao = MemoryAuthDao::default();
+        let config = AuthConfig {
+            challenge_ttl_secs: 60,
+            session_ttl_secs: 60,
+        };
+        let keypair = test_keypair()?;
+        let address = Address::new(keypair.public.to_bytes());
+
+        let challenge = create_challenge(
+            &dao,
+            &config,
+            &ChallengeRequestJson {
+                address: address.to_string(),
+            },
+        )
+        .await?;
+
+        let mut message = b"MX".to_vec();
+        message.extend_from_slice(challenge.message.as_bytes());
+        let verify = VerifyChallengeJson {
+            address: address.to_string(),
+            nonce: challenge.nonce,
+            signature: BASE64.encode(&keypair.sign(&message).to_bytes()),
+        };
+
+        let session = verify_challenge(&dao, &config, &verify).await?;
+        assert_eq!(address, authenticate(&dao, &session.token).await?);
+
+        // replay
+        let res = verify_challenge(&dao, &config, &verify).await;
+        assert!(matches!(res, Err(ServiceError::Unauthorized(_))));
+        Ok(())
+    }
+
+    fn test_keypair() -> Result<Keypair> {
+        let secret = SecretKey::from_bytes(&[7; 32])?;
+        let public = PublicKey::from(&secret);
+        Ok(Keypair { secret, public })
+    }
+
+    #[derive(Default)]
+    struct MemoryAuthDao {
+        challenges: Mutex<HashMap<String, (Address, DateTime<Utc>)>>,
+        sessions: Mutex<HashMap<String, (Address, DateTime<Utc>)>>,
+    }
+
+    #[async_trait]
+    impl AuthDao for MemoryAuthDao {
+        async fn save_challenge(
+            &self,
+            nonce: &str,
+            address: &Address,
+            expires_at: &DateTime<Utc>,
+        ) -> Result<(), ServiceError> {
+            self.challenges
+                .lock()
+                .unwrap()
+                .insert(nonce.to_owned(), (*address, *expires_at));
+            Ok(())
+        }
+
+        async fn take_challenge(
+            &self,
+            nonce: &str,
+            address: &Address,
+        ) -> Result<bool, ServiceError> {
+            let mut challenges = self.challenges.lock().unwrap();
+            Ok(match challenges.get(nonce) {
+                Some((a, expires_at)) if a == address => {
+                    let valid = *expires_at > Utc::now();
+                    challenges.remove(nonce);
+                    valid
+                }
+                _ => false,
+            })
+        }
+
+        async fn save_session(
+            &self,
+            token_hash: &str,
+            address: &Address,
+            expires_at: &DateTime<Utc>,
+        ) -> Result<(), ServiceError> {
+            self.sessions
+                .lock()
+                .unwrap()
+                .insert(token_hash.to_owned(), (*address, *expires_at));
+            Ok(())
+        }
+
+        async fn load_session(&self, token_hash: &str) -> Result<Option<Address>, ServiceError> {
+            Ok(self
+                .sessions
+                .lock()
+                .unwrap()
+                .get(token_hash)
+                .filter(|(_, expires_at)| *expires_at > Utc::now())
+                .map(|(address, _)| *address))
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::convert::TryFrom;
+
+use algonaut::core::Address;
+use core_::flows::create_project::model::Project;
+use data_encoding::BASE64;
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use sha2::{Digest, Sha256};
+
+use crate::error::ServiceError;
+
+/// Prefix added by Algorand wallets when signing arbitrary data (algosdk's `signBytes`)
+const SIGNED_BYTES_PREFIX: &[u8] = b"MX";
+
+/// Verifies that `signature` (base64) was created by the account with `address`, signing `data` with a wallet.
+/// The public key is the address itself.
+pub fn verify_signature(
+    address: &Address,
+    data: &[u8],
+    signature: &str,
+) -> Result<(), ServiceError> {
+    let signature = BASE64
+        .decode(signature.as_bytes())
+        .map_err(|_| ServiceError::InvalidInput("Signature isn't valid base64".to_owned()))?;
+    let signature = Signature::try_from(signature.as_slice())
+        .map_err(|_| ServiceError::InvalidInput("Invalid signature format".to_owned()))?;
+    let public_key = PublicKey::from_bytes(&address.0)
+        .map_err(|e| ServiceError::InvalidInput(format!("Invalid public key: {}", e)))?;
+
+    let mut message = SIGNED_BYTES_PREFIX.to_vec();
+    message.extend_from_slice(data);
+
+    public_key.verify(&message, &signature).map_err(|_| {
+        ServiceError::Unauthorized(format!("Signature doesn't match address: {}", address))
+    })
+}
+
+/// Data the creator signs to prove that they submitted the project: the SHA-256 of the project's fields,
+/// one "key=value" per line (\n), in this order, without a trailing new line:
+/// uuid, name, token_name, share_count, investors_share, asset_price (microalgos), creator,
+/// shares_asset_id, central_app_id, invest_escrow, staking_escrow, central_escrow, customer_escrow (addresses).
+/// The escrow addresses are hashes of the programs, so the programs are covered too.
+pub fn project_signing_data(project: &Project) -> Vec<u8> {
+    let fields = [
+        format!("uuid={}", project.uuid),
+        format!("name={}", project.specs.name),
+        format!("token_name={}", project.specs.shares.token_name),
+        format!("share_count={}", project.specs.shares.count),
+        format!("investors_share={}", project.specs.investors_share),
+        format!("asset_price={}", project.specs.asset_price.0),
+        format!("creator={}", project.creator),
+        format!("shares_asset_id={}", project.shares_asset_id),
+        format!("central_app_id={}", project.central_app_id),
+        format!("invest_escrow={}", project.invest_escrow.address()),
+        format!("staking_escrow={}", project.staking_escrow.address()),
+        format!("central_escrow={}", project.central_escrow.address()),
+        format!("customer_escrow={}", project.customer_escrow.address()),
+    ];
+    Sha256::digest(fields.join("\n").as_bytes()).to_vec()
+}
+
+/// Verifies that the project was signed by its creator
+pub fn verify_project_signature(project: &Project, signature: &str) -> Result<(), ServiceError> {
+    verify_signature(&project.creator, &project_signing_data(project), signature)
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::{project_signing_data, verify_project_signature, SIGNED_BYTES_PREFIX};
+    use crate::{error::ServiceError, test_data::project_json};
+    use algonaut::core::Address;
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use data_encoding::BASE64;
+    use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+    use tokio::test;
+
+    #[test]
+    async fn test_project_signed_by_creator_is_accepted() -> Result<()> {
+        let (keypair, project) = project_with_test_creator()?;
+        let signature = sign(&keypair, &project_signing_data(&project));
+
+        verify_project_signature(&project, &signature)?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_modified_project_is_rejected() -> Result<()> {
+        let (keypair, mut project) = project_with_test_creator()?;
+        let signature = sign(&keypair, &project_signing_data(&project));
+        project.central_app_id += 1;
+
+        let res = verify_project_signature(&project, &signature);
+        assert!(matches!(res, Err(ServiceError::Unauthorized(_))));
+        Ok(())
+    }
+
+    fn project_with_test_creator() -> Result<(Keypair, Project)> {
+        let secret = SecretKey::from_bytes(&[7; 32])?;
+        let public = PublicKey::from(&secret);
+        let mut project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        project.creator = Address::new(public.to_bytes());
+        Ok((Keypair { secret, public }, project))
+    }
+
+    fn sign(keypair: &Keypair, data: &[u8]) -> String {
+        let mut message = SIGNED_BYTES_PREFIX.to_vec();
+        message.extend_from_slice(data);
+        BASE64.encode(&keypair.sign(&message).to_bytes())
+    }
+}
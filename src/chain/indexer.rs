@@ -0,0 +1,421 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use url::Url;
+
+use crate::error::ServiceError;
+
+/// Read access to the indexed chain history (Algorand indexer).
+#[async_trait]
+pub trait ChainIndexer: Sync + Send {
+    /// The last round the indexer has processed
+    async fn current_round(&self) -> Result<u64, ServiceError>;
+    /// All the transactions matching the query (follows the indexer's pagination).
+    async fn transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> Result<Vec<IndexerTransaction>, ServiceError>;
+    /// The calls to the app confirmed in the rounds (inclusive)
+    async fn app_calls(
+        &self,
+        app_id: u64,
+        min_round: u64,
+        max_round: u64,
+    ) -> Result<Vec<IndexerTransaction>, ServiceError>;
+    /// None if the transaction isn't indexed (not confirmed, or confirmed after the indexer's current round)
+    async fn transaction(&self, txid: &str) -> Result<Option<IndexerTransaction>, ServiceError>;
+    /// The amount of the asset the account holds, 0 if the account doesn't exist or isn't opted in
+    async fn asset_amount(&self, address: &str, asset_id: u64) -> Result<u64, ServiceError>;
+    /// The accounts holding the asset (amount > 0), with their amounts
+    async fn asset_balances(&self, asset_id: u64) -> Result<Vec<AssetBalance>, ServiceError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressRole {
+    Sender,
+    Receiver,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxType {
+    Payment,
+    AssetTransfer,
+    AppCall,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransactionQuery {
+    pub address: String,
+    pub address_role: AddressRole,
+    pub tx_type: TxType,
+    pub asset_id: Option<u64>,
+    /// Inclusive
+    pub min_round: u64,
+    /// Inclusive
+    pub max_round: u64,
+}
+
+/// The subset of the indexer's transaction we use
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct IndexerTransaction {
+    pub id: String,
+    /// Base64, present if the transaction was submitted in a group
+    pub group: Option<String>,
+    pub confirmed_round: u64,
+    /// Unix timestamp (seconds)
+    pub round_time: i64,
+    pub sender: String,
+    pub note: Option<String>,
+    pub payment_transaction: Option<PaymentTransaction>,
+    pub asset_transfer_transaction: Option<AssetTransferTransaction>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PaymentTransaction {
+    pub receiver: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetTransferTransaction {
+    pub receiver: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssetBalance {
+    pub address: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TransactionsResponse {
+    next_token: Option<String>,
+    transactions: Vec<IndexerTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransactionResponse {
+    transaction: IndexerTransaction,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AssetBalancesResponse {
+    next_token: Option<String>,
+    balances: Vec<AssetBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountResponse {
+    account: AccountJson,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountJson {
+    #[serde(default)]
+    assets: Vec<AssetHoldingJson>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct AssetHoldingJson {
+    asset_id: u64,
+    amount: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct HealthResponse {
+    round: u64,
+}
+
+/// Results per request (the indexer's max. is 1000)
+const PAGE_SIZE: u64 = 1000;
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+pub struct IndexerClient {
+    client: Client,
+    url: Url,
+    token: String,
+}
+
+impl IndexerClient {
+    pub fn new(url: &str, token: &str) -> Result<IndexerClient> {
+        Ok(IndexerClient {
+            client: Client::builder()
+                .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+                .build()?,
+            url: Url::parse(url)?,
+            token: token.to_owned(),
+        })
+    }
+
+    /// All the transactions matching the params (follows the indexer's pagination).
+    async fn search_transactions(
+        &self,
+        params: &[(&str, String)],
+    ) -> Result<Vec<IndexerTransaction>, ServiceError> {
+        let mut transactions = vec![];
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut request = self
+                .get("v2/transactions")?
+                .query(params)
+                .query(&[("limit", PAGE_SIZE.to_string())]);
+            if let Some(next_token) = &next_token {
+                request = request.query(&[("next", next_token)]);
+            }
+            let page: TransactionsResponse = send(request).await?;
+            let is_last_page = page.transactions.is_empty() || page.next_token.is_none();
+            transactions.extend(page.transactions);
+            if is_last_page {
+                break;
+            }
+            next_token = page.next_token;
+        }
+        Ok(transactions)
+    }
+
+    fn get(&self, path: &str) -> Result<RequestBuilder, ServiceError> {
+        let url = self
+            .url
+            .join(path)
+            .map_err(|e| ServiceError::Internal(format!("Invalid indexer url: {}", e)))?;
+        Ok(self
+            .client
+            .get(url)
+            .header("X-Indexer-API-Token", &self.token))
+    }
+}
+
+#[async_trait]
+impl ChainIndexer for IndexerClient {
+    async fn current_round(&self) -> Result<u64, ServiceError> {
+        let health: HealthResponse = send(self.get("health")?).await?;
+        Ok(health.round)
+    }
+
+    async fn transactions(
+        &self,
+        query: &TransactionQuery,
+    ) -> Result<Vec<IndexerTransaction>, ServiceError> {
+        let mut params = vec![
+            ("address", query.address.clone()),
+            ("address-role", query.address_role.as_param().to_owned()),
+            ("tx-type", query.tx_type.as_param().to_owned()),
+            ("min-round", query.min_round.to_string()),
+            ("max-round", query.max_round.to_string()),
+        ];
+        if let Some(asset_id) = query.asset_id {
+            params.push(("asset-id", asset_id.to_string()));
+        }
+        self.search_transactions(&params).await
+    }
+
+    async fn app_calls(
+        &self,
+        app_id: u64,
+        min_round: u64,
+        max_round: u64,
+    ) -> Result<Vec<IndexerTransaction>, ServiceError> {
+        self.search_transactions(&[
+            ("application-id", app_id.to_string()),
+            ("tx-type", TxType::AppCall.as_param().to_owned()),
+            ("min-round", min_round.to_string()),
+            ("max-round", max_round.to_string()),
+        ])
+        .await
+    }
+
+    async fn transaction(&self, txid: &str) -> Result<Option<IndexerTransaction>, ServiceError> {
+        let res: Option<TransactionResponse> =
+            send_optional(self.get(&format!("v2/transactions/{}", txid))?).await?;
+        Ok(res.map(|res| res.transaction))
+    }
+
+    async fn asset_amount(&self, address: &str, asset_id: u64) -> Result<u64, ServiceError> {
+        let account: Option<AccountResponse> =
+            send_optional(self.get(&format!("v2/accounts/{}", address))?).await?;
+        Ok(account
+            .and_then(|res| {
+                res.account
+                    .assets
+                    .into_iter()
+                    .find(|holding| holding.asset_id == asset_id)
+            })
+            .map(|holding| holding.amount)
+            .unwrap_or_default())
+    }
+
+    async fn asset_balances(&self, asset_id: u64) -> Result<Vec<AssetBalance>, ServiceError> {
+        let path = format!("v2/assets/{}/balances", asset_id);
+        let params = [
+            ("currency-greater-than", "0".to_owned()),
+            ("limit", PAGE_SIZE.to_string()),
+        ];
+
+        let mut balances = vec![];
+        let mut next_token: Option<String> = None;
+        loop {
+            let mut request = self.get(&path)?.query(&params);
+            if let Some(next_token) = &next_token {
+                request = request.query(&[("next", next_token)]);
+            }
+            let page: AssetBalancesResponse = send(request).await?;
+            let is_last_page = page.balances.is_empty() || page.next_token.is_none();
+            balances.extend(page.balances);
+            if is_last_page {
+                break;
+            }
+            next_token = page.next_token;
+        }
+        Ok(balances)
+    }
+}
+
+async fn send<T>(request: RequestBuilder) -> Result<T, ServiceError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let upstream_error = |e: reqwest::Error| ServiceError::Upstream(format!("Indexer: {}", e));
+    request
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(upstream_error)?
+        .json()
+        .await
+        .map_err(upstream_error)
+}
+
+/// Like send, with None for 404
+async fn send_optional<T>(request: RequestBuilder) -> Result<Option<T>, ServiceError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let upstream_error = |e: reqwest::Error| ServiceError::Upstream(format!("Indexer: {}", e));
+    let res = request.send().await.map_err(upstream_error)?;
+    if res.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(
+        res.error_for_status()
+            .map_err(upstream_error)?
+            .json()
+            .await
+            .map_err(upstream_error)?,
+    ))
+}
+
+impl IndexerTransaction {
+    pub fn confirmed_at(&self) -> Result<DateTime<Utc>> {
+        Utc.timestamp_opt(self.round_time, 0)
+            .single()
+            .ok_or_else(|| anyhow!("Invalid round time: {} in tx: {}", self.round_time, self.id))
+    }
+}
+
+impl AddressRole {
+    fn as_param(&self) -> &'static str {
+        match self {
+            AddressRole::Sender => "sender",
+            AddressRole::Receiver => "receiver",
+        }
+    }
+}
+
+impl TxType {
+    fn as_param(&self) -> &'static str {
+        match self {
+            TxType::Payment => "pay",
+            TxType::AssetTransfer => "axfer",
+            TxType::AppCall => "appl",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{AddressRole, ChainIndexer, IndexerClient, TransactionQuery, TxType};
+    use anyhow::Result;
+    use serde_json::json;
+    use tokio::test;
+    use warp::Filter;
+
+    #[test]
+    async fn test_loads_all_transaction_pages() -> Result<()> {
+        let indexer = client_with_stub_indexer()?;
+
+        assert_eq!(120, indexer.current_round().await?);
+
+        let transactions = indexer
+            .transactions(&TransactionQuery {
+                address: "ADDRESS".to_owned(),
+                address_role: AddressRole::Sender,
+                tx_type: TxType::Payment,
+                asset_id: None,
+                min_round: 0,
+                max_round: 120,
+            })
+            .await?;
+        let ids: Vec<&str> = transactions.iter().map(|tx| tx.id.as_str()).collect();
+        assert_eq!(vec!["TX1", "TX2"], ids);
+        assert_eq!(
+            Some(50),
+            transactions[1]
+                .payment_transaction
+                .as_ref()
+                .map(|p| p.amount)
+        );
+
+        Ok(())
+    }
+
+    /// Starts an HTTP server returning 2 pages of transactions (one per page)
+    fn client_with_stub_indexer() -> Result<IndexerClient> {
+        let health = warp::path!("health").map(|| warp::reply::json(&json!({ "round": 120 })));
+        let transactions = warp::path!("v2" / "transactions")
+            .and(warp::query::<HashMap<String, String>>())
+            .map(|params: HashMap<String, String>| {
+                let page = match params.get("next").map(String::as_str) {
+                    None => json!({
+                        "current-round": 120,
+                        "next-token": "page2",
+                        "transactions": [transaction_json("TX1", 10)],
+                    }),
+                    Some("page2") => json!({
+                        "current-round": 120,
+                        "next-token": "page3",
+                        "transactions": [transaction_json("TX2", 50)],
+                    }),
+                    Some(_) => json!({ "current-round": 120, "transactions": [] }),
+                };
+                warp::reply::json(&page)
+            });
+
+        let (addr, server) =
+            warp::serve(health.or(transactions)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        IndexerClient::new(&format!("http://{}", addr), "")
+    }
+
+    fn transaction_json(id: &str, amount: u64) -> serde_json::Value {
+        json!({
+            "id": id,
+            "confirmed-round": 100,
+            "round-time": 1640995200,
+            "sender": "ADDRESS",
+            "tx-type": "pay",
+            "payment-transaction": { "receiver": "RECEIVER", "amount": amount },
+        })
+    }
+}
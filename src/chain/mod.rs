@@ -0,0 +1,49 @@
+use algonaut::{
+    algod::v2::Algod,
+    error::{AlgonautError, RequestError, RequestErrorDetails},
+};
+use anyhow::Result;
+use serde::Deserialize;
+
+use self::indexer::IndexerClient;
+
+pub mod indexer;
+pub mod project_verifier;
+pub mod signed_tx;
+pub mod transactions;
+pub mod tx_submitter;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainConfig {
+    pub algod_url: String,
+    pub algod_token: String,
+    pub indexer_url: String,
+    pub indexer_token: String,
+    /// Whether submitted projects are verified against the chain before being saved
+    pub verify_projects: bool,
+}
+
+pub fn create_algod(config: &ChainConfig) -> Result<Algod> {
+    Ok(Algod::new(&config.algod_url, &config.algod_token)?)
+}
+
+pub fn create_indexer(config: &ChainConfig) -> Result<IndexerClient> {
+    IndexerClient::new(&config.indexer_url, &config.indexer_token)
+}
+
+/// Whether algod answered the request with 404.
+/// Other failures (no response, timeout, server errors) don't tell anything about the requested entity.
+pub fn is_algod_not_found(error: &AlgonautError) -> bool {
+    algod_error_status(error) == Some(404)
+}
+
+/// The HTTP status of algod's error response, None if there was no response (e.g. connection error, timeout)
+pub fn algod_error_status(error: &AlgonautError) -> Option<u16> {
+    match error {
+        AlgonautError::Request(RequestError {
+            details: RequestErrorDetails::Http { status, .. },
+            ..
+        }) => Some(*status),
+        _ => None,
+    }
+}
@@ -0,0 +1,295 @@
+use std::sync::Arc;
+
+use algonaut::{algod::v2::Algod, core::Address, error::AlgonautError};
+use async_trait::async_trait;
+use core_::flows::create_project::model::Project;
+
+use crate::{error::ServiceError, validation::FieldError};
+
+use super::is_algod_not_found;
+
+/// Verifies that what a project declares corresponds to the chain state.
+#[async_trait]
+pub trait ProjectVerifier: Sync + Send {
+    async fn verify(&self, project: &Project) -> Result<(), ServiceError>;
+}
+
+pub struct ProjectVerifierImpl {
+    pub algod: Arc<Algod>,
+}
+
+/// For environments where projects aren't verified
+pub struct NoopProjectVerifier {}
+
+#[async_trait]
+impl ProjectVerifier for NoopProjectVerifier {
+    async fn verify(&self, _project: &Project) -> Result<(), ServiceError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProjectVerifier for ProjectVerifierImpl {
+    async fn verify(&self, project: &Project) -> Result<(), ServiceError> {
+        let mut errors = vec![];
+
+        match self.algod.asset_information(project.shares_asset_id).await {
+            Ok(asset) => {
+                if asset.params.unit_name.as_deref()
+                    != Some(project.specs.shares.token_name.as_str())
+                {
+                    errors.push(FieldError::new(
+                        "specs.shares.token_name",
+                        "Doesn't match the shares asset",
+                    ));
+                }
+                if asset.params.total != project.specs.shares.count {
+                    errors.push(FieldError::new(
+                        "specs.shares.count",
+                        "Doesn't match the shares asset",
+                    ));
+                }
+            }
+            Err(e) if is_algod_not_found(&e) => {
+                errors.push(FieldError::new("shares_asset_id", "Asset not found"));
+            }
+            Err(e) => return Err(upstream_error("asset", e)),
+        }
+
+        match self
+            .algod
+            .application_information(project.central_app_id)
+            .await
+        {
+            Ok(app) => {
+                if app.params.creator.to_string() != project.creator.to_string() {
+                    errors.push(FieldError::new(
+                        "central_app_id",
+                        "Wasn't created by the project's creator",
+                    ));
+                }
+            }
+            Err(e) if is_algod_not_found(&e) => {
+                errors.push(FieldError::new("central_app_id", "App not found"));
+            }
+            Err(e) => return Err(upstream_error("app", e)),
+        }
+
+        // invest and staking escrows hold (respectively receive) the shares
+        self.verify_escrow(
+            "invest_escrow",
+            project.invest_escrow.address(),
+            Some(project.shares_asset_id),
+            &mut errors,
+        )
+        .await?;
+        self.verify_escrow(
+            "staking_escrow",
+            project.staking_escrow.address(),
+            Some(project.shares_asset_id),
+            &mut errors,
+        )
+        .await?;
+        self.verify_escrow(
+            "central_escrow",
+            project.central_escrow.address(),
+            None,
+            &mut errors,
+        )
+        .await?;
+        self.verify_escrow(
+            "customer_escrow",
+            project.customer_escrow.address(),
+            None,
+            &mut errors,
+        )
+        .await?;
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ServiceError::Validation(errors))
+        }
+    }
+}
+
+impl ProjectVerifierImpl {
+    async fn verify_escrow(
+        &self,
+        field: &str,
+        address: &Address,
+        opted_in_asset: Option<u64>,
+        errors: &mut Vec<FieldError>,
+    ) -> Result<(), ServiceError> {
+        match self.algod.account_information(address).await {
+            Ok(account) => {
+                if account.amount.0 == 0 {
+                    errors.push(FieldError::new(field, "Not funded"));
+                }
+                if let Some(asset_id) = opted_in_asset {
+                    if !account.assets.iter().any(|a| a.asset_id == asset_id) {
+                        errors.push(FieldError::new(field, "Not opted in to the shares asset"));
+                    }
+                }
+            }
+            Err(e) if is_algod_not_found(&e) => {
+                errors.push(FieldError::new(field, "Account not found"));
+            }
+            Err(e) => return Err(upstream_error("account", e)),
+        }
+        Ok(())
+    }
+}
+
+/// The project can't be verified: not a validation error
+fn upstream_error(entity: &str, e: AlgonautError) -> ServiceError {
+    ServiceError::Upstream(format!("Couldn't load {} from algod: {}", entity, e))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{convert::TryInto, sync::Arc};
+
+    use super::{ProjectVerifier, ProjectVerifierImpl};
+    use crate::{error::ServiceError, test_data::project_json};
+    use algonaut::algod::v2::Algod;
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use serde_json::json;
+    use tokio::test;
+    use warp::{http::StatusCode, Filter};
+
+    #[test]
+    async fn test_verify_project_matching_the_chain() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let verifier =
+            verifier_with_stub_algod(&project, project.specs.shares.count, StatusCode::OK);
+
+        verifier.verify(&project).await?;
+        Ok(())
+    }
+
+    #[test]
+    async fn test_verify_project_with_wrong_share_count() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let verifier =
+            verifier_with_stub_algod(&project, project.specs.shares.count + 1, StatusCode::OK);
+
+        match verifier.verify(&project).await {
+            Err(ServiceError::Validation(errors)) => {
+                assert_eq!(1, errors.len());
+                assert_eq!("specs.shares.count", errors[0].field);
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        Ok(())
+    }
+
+    #[test]
+    async fn test_verify_project_with_missing_asset() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let verifier =
+            verifier_with_stub_algod(&project, project.specs.shares.count, StatusCode::NOT_FOUND);
+
+        match verifier.verify(&project).await {
+            Err(ServiceError::Validation(errors)) => {
+                assert_eq!(1, errors.len());
+                assert_eq!("shares_asset_id", errors[0].field);
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        Ok(())
+    }
+
+    #[test]
+    async fn test_verify_project_with_algod_error() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let verifier = verifier_with_stub_algod(
+            &project,
+            project.specs.shares.count,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        );
+
+        // not the project's fault
+        let res = verifier.verify(&project).await;
+
+        assert!(matches!(res, Err(ServiceError::Upstream(_))));
+        Ok(())
+    }
+
+    /// Starts an HTTP server serving the algod endpoints used by the verifier,
+    /// with the chain state corresponding to the project, except for the shares asset's total.
+    /// The asset is answered with `asset_status` (its error if not OK).
+    fn verifier_with_stub_algod(
+        project: &Project,
+        asset_total: u64,
+        asset_status: StatusCode,
+    ) -> ProjectVerifierImpl {
+        let asset_id = project.shares_asset_id;
+        let creator = project.creator.to_string();
+        let token_name = project.specs.shares.token_name.clone();
+        let asset = warp::path!("v2" / "assets" / u64).map(move |id: u64| {
+            let asset = json!({
+                "index": id,
+                "params": {
+                    "creator": creator,
+                    "decimals": 0,
+                    "default-frozen": false,
+                    "name": token_name,
+                    "total": asset_total,
+                    "unit-name": token_name
+                }
+            });
+            match asset_status {
+                StatusCode::OK => warp::reply::with_status(warp::reply::json(&asset), asset_status),
+                _ => warp::reply::with_status(
+                    warp::reply::json(&json!({ "message": "asset error" })),
+                    asset_status,
+                ),
+            }
+        });
+
+        let creator = project.creator.to_string();
+        let app = warp::path!("v2" / "applications" / u64).map(move |id: u64| {
+            warp::reply::json(&json!({
+                "id": id,
+                "params": {
+                    "approval-program": "BIEB",
+                    "clear-state-program": "BIEB",
+                    "creator": creator,
+                    "global-state": [],
+                    "global-state-schema": { "num-byte-slice": 0, "num-uint": 0 },
+                    "local-state-schema": { "num-byte-slice": 0, "num-uint": 0 }
+                }
+            }))
+        });
+
+        let account = warp::path!("v2" / "accounts" / String).map(move |address: String| {
+            warp::reply::json(&json!({
+                "address": address,
+                "amount": 1_000_000,
+                "amount-without-pending-rewards": 1_000_000,
+                "assets": [{ "amount": 0, "asset-id": asset_id, "creator": "", "is-frozen": false }],
+                "pending-rewards": 0,
+                "reward-base": 0,
+                "rewards": 0,
+                "round": 1000,
+                "status": "Offline"
+            }))
+        });
+
+        let (addr, server) =
+            warp::serve(asset.or(app).or(account)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        ProjectVerifierImpl {
+            algod: Arc::new(
+                Algod::new(
+                    &format!("http://{}", addr),
+                    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                )
+                .unwrap(),
+            ),
+        }
+    }
+}
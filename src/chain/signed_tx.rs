@@ -0,0 +1,184 @@
+use std::convert::TryFrom;
+
+use algonaut::core::Address;
+use data_encoding::BASE32_NOPAD;
+use rmpv::{decode::read_value, encode::write_value, Value};
+use sha2::{Digest, Sha512Trunc256};
+
+use crate::error::ServiceError;
+
+/// Prefix of the bytes hashed to get the transaction id
+const TXID_PREFIX: &[u8] = b"TX";
+
+/// The fields of a signed transaction (msgpack encoded, as submitted to algod) that we check before relaying it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTx {
+    pub txid: String,
+    pub sender: Address,
+    /// Of the payment or asset transfer
+    pub receiver: Option<Address>,
+    /// Of the app call
+    pub app_id: Option<u64>,
+    /// Set if the transaction is part of a group
+    pub group: Option<Vec<u8>>,
+    pub last_valid: u64,
+    pub signature: TxSignature,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxSignature {
+    Single,
+    Multi,
+    /// The program of the logic signature
+    Logic(Vec<u8>),
+}
+
+pub fn decode_signed_tx(bytes: &[u8]) -> Result<SignedTx, ServiceError> {
+    let invalid = |reason: &str| {
+        ServiceError::InvalidInput(format!("Invalid signed transaction: {}", reason))
+    };
+
+    let mut reader = bytes;
+    let value = read_value(&mut reader).map_err(|_| invalid("not msgpack"))?;
+    if !reader.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+    let signed_tx = value.as_map().ok_or_else(|| invalid("not a map"))?;
+    let tx = field(signed_tx, "txn").ok_or_else(|| invalid("missing txn"))?;
+    let tx_fields = tx.as_map().ok_or_else(|| invalid("txn is not a map"))?;
+
+    let sender = field(tx_fields, "snd")
+        .and_then(address)
+        .ok_or_else(|| invalid("invalid snd"))?;
+    let receiver = match field(tx_fields, "rcv").or_else(|| field(tx_fields, "arcv")) {
+        Some(receiver) => Some(address(receiver).ok_or_else(|| invalid("invalid receiver"))?),
+        None => None,
+    };
+    let app_id = match field(tx_fields, "apid") {
+        Some(app_id) => Some(app_id.as_u64().ok_or_else(|| invalid("invalid apid"))?),
+        None => None,
+    };
+    let group = match field(tx_fields, "grp") {
+        Some(group) => Some(
+            group
+                .as_slice()
+                .filter(|bytes| bytes.len() == 32)
+                .ok_or_else(|| invalid("invalid grp"))?
+                .to_vec(),
+        ),
+        None => None,
+    };
+    let last_valid = field(tx_fields, "lv")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| invalid("invalid lv"))?;
+
+    let signature = if field(signed_tx, "sig").is_some() {
+        TxSignature::Single
+    } else if field(signed_tx, "msig").is_some() {
+        TxSignature::Multi
+    } else if let Some(lsig) = field(signed_tx, "lsig") {
+        let program = lsig
+            .as_map()
+            .and_then(|lsig| field(lsig, "l"))
+            .and_then(Value::as_slice)
+            .ok_or_else(|| invalid("invalid lsig"))?;
+        TxSignature::Logic(program.to_vec())
+    } else {
+        return Err(invalid("not signed"));
+    };
+
+    Ok(SignedTx {
+        txid: txid(tx)?,
+        sender,
+        receiver,
+        app_id,
+        group,
+        last_valid,
+        signature,
+    })
+}
+
+/// Hash of the encoded transaction. Submitted transactions are canonically encoded
+/// (sorted keys, no empty values), so re-encoding the decoded value gives the same bytes.
+fn txid(tx: &Value) -> Result<String, ServiceError> {
+    let mut bytes = TXID_PREFIX.to_vec();
+    write_value(&mut bytes, tx).map_err(|e| ServiceError::Internal(e.to_string()))?;
+    Ok(BASE32_NOPAD.encode(&Sha512Trunc256::digest(&bytes)))
+}
+
+fn address(value: &Value) -> Option<Address> {
+    value
+        .as_slice()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .map(Address::new)
+}
+
+fn field<'a>(map: &'a [(Value, Value)], key: &str) -> Option<&'a Value> {
+    map.iter()
+        .find(|(k, _)| k.as_str() == Some(key))
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::{decode_signed_tx, TxSignature};
+    use crate::{
+        chain::transactions::{invest_txs, GroupTx},
+        error::ServiceError,
+        test_data::{project_json, stub_algod},
+    };
+    use algonaut::core::{Address, ToMsgPack};
+    use anyhow::{anyhow, Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_decode_escrow_tx() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let txs = invest_txs(&stub_algod()?, &project, &investor()?, 10).await?;
+        let shares_xfer = match &txs[3] {
+            GroupTx::Signed(signed) => signed.clone(),
+            tx => return Err(anyhow!("Unexpected tx: {:?}", tx)),
+        };
+
+        let decoded = decode_signed_tx(&shares_xfer.to_msg_pack()?)?;
+
+        assert_eq!(*project.invest_escrow.address(), decoded.sender);
+        assert_eq!(Some(*project.staking_escrow.address()), decoded.receiver);
+        assert_eq!(None, decoded.app_id);
+        assert_eq!(
+            shares_xfer.transaction.group.map(|group| group.0.to_vec()),
+            decoded.group
+        );
+        assert_eq!(2000, decoded.last_valid);
+        assert_eq!(
+            TxSignature::Logic(project.invest_escrow.program.0.clone()),
+            decoded.signature
+        );
+        assert_eq!(52, decoded.txid.len());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_refuses_unsigned_tx() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let txs = invest_txs(&stub_algod()?, &project, &investor()?, 10).await?;
+        let app_call = match &txs[0] {
+            GroupTx::Unsigned(tx) => tx.clone(),
+            tx => return Err(anyhow!("Unexpected tx: {:?}", tx)),
+        };
+
+        let res = decode_signed_tx(&app_call.to_msg_pack()?);
+
+        assert!(matches!(res, Err(ServiceError::InvalidInput(_))));
+        Ok(())
+    }
+
+    fn investor() -> Result<Address> {
+        "MKRBTLNZRS3UZZDS5OWPLP7YPHUDNKXFUFN5PNCJ3P2XRG74HNOGY6XOYQ"
+            .parse()
+            .map_err(Error::msg)
+    }
+}
@@ -0,0 +1,377 @@
+use algonaut::{
+    algod::v2::Algod,
+    core::{Address, MicroAlgos},
+    transaction::{SignedTransaction, Transaction},
+};
+use core_::flows::{
+    create_project::model::Project,
+    harvest::logic::{harvest, HarvestToSign},
+    invest::logic::{invest_txs as core_invest_txs, InvestToSign},
+    pay_project::logic::{pay_project, PayProjectToSign},
+    stake::logic::{stake, StakeToSign},
+    unstake::logic::{unstake, UnstakeToSign},
+};
+
+use crate::error::ServiceError;
+
+/// A transaction of a group: the user's ones are left to sign,
+/// the escrows' ones are signed with the escrow's logic signature.
+#[derive(Debug, Clone)]
+pub enum GroupTx {
+    Unsigned(Transaction),
+    Signed(SignedTransaction),
+}
+
+// The groups are built by core, with the layouts the escrows' programs check,
+// the functions here only put the transactions in group order.
+
+/// Investing: the investor pays the price of the shares to the central escrow
+/// and the invest escrow transfers the shares to the staking escrow, where they're locked for the investor.
+///
+/// Group: app setup, payment to the central escrow, shares opt-in, shares transfer (invest escrow),
+/// votes transfer (invest escrow), invest escrow fee payment.
+pub async fn invest_txs(
+    algod: &Algod,
+    project: &Project,
+    investor: &Address,
+    share_count: u64,
+) -> Result<Vec<GroupTx>, ServiceError> {
+    // core doesn't check the overflow
+    if project
+        .specs
+        .asset_price
+        .0
+        .checked_mul(share_count)
+        .is_none()
+    {
+        return Err(ServiceError::InvalidInput(format!(
+            "Too many shares: {}",
+            share_count
+        )));
+    }
+    let to_sign = core_invest_txs(
+        algod,
+        project,
+        investor,
+        &project.staking_escrow,
+        project.central_app_id,
+        project.shares_asset_id,
+        share_count,
+        project.specs.asset_price,
+    )
+    .await
+    .map_err(build_error)?;
+
+    let InvestToSign {
+        central_app_setup_tx,
+        payment_tx,
+        shares_asset_optin_tx,
+        shares_xfer_tx,
+        votes_xfer_tx,
+        pay_escrow_fee_tx,
+        ..
+    } = to_sign;
+    Ok(vec![
+        GroupTx::Unsigned(central_app_setup_tx),
+        GroupTx::Unsigned(payment_tx),
+        GroupTx::Unsigned(shares_asset_optin_tx),
+        GroupTx::Signed(shares_xfer_tx),
+        GroupTx::Signed(votes_xfer_tx),
+        GroupTx::Unsigned(pay_escrow_fee_tx),
+    ])
+}
+
+/// Group: app call, shares transfer to the staking escrow.
+pub async fn stake_txs(
+    algod: &Algod,
+    project: &Project,
+    investor: &Address,
+    share_count: u64,
+) -> Result<Vec<GroupTx>, ServiceError> {
+    let StakeToSign {
+        central_app_call_setup_tx,
+        shares_xfer_tx,
+    } = stake(
+        algod,
+        *investor,
+        share_count,
+        project.shares_asset_id,
+        project.central_app_id,
+        &project.staking_escrow,
+    )
+    .await
+    .map_err(build_error)?;
+
+    Ok(vec![
+        GroupTx::Unsigned(central_app_call_setup_tx),
+        GroupTx::Unsigned(shares_xfer_tx),
+    ])
+}
+
+/// Group: app opt-out, shares transfer (staking escrow) to the investor, staking escrow fee payment.
+pub async fn unstake_txs(
+    algod: &Algod,
+    project: &Project,
+    investor: &Address,
+    share_count: u64,
+) -> Result<Vec<GroupTx>, ServiceError> {
+    let UnstakeToSign {
+        central_app_optout_tx,
+        shares_xfer_tx,
+        pay_shares_xfer_fee_tx,
+    } = unstake(
+        algod,
+        *investor,
+        share_count,
+        project.shares_asset_id,
+        project.central_app_id,
+        &project.staking_escrow,
+    )
+    .await
+    .map_err(build_error)?;
+
+    Ok(vec![
+        GroupTx::Unsigned(central_app_optout_tx),
+        GroupTx::Signed(shares_xfer_tx),
+        GroupTx::Unsigned(pay_shares_xfer_fee_tx),
+    ])
+}
+
+/// The app checks that the amount doesn't exceed the investor's dividend.
+///
+/// Group: app call, payment (central escrow) to the investor, central escrow fee payment.
+pub async fn harvest_txs(
+    algod: &Algod,
+    project: &Project,
+    investor: &Address,
+    amount: MicroAlgos,
+) -> Result<Vec<GroupTx>, ServiceError> {
+    let HarvestToSign {
+        app_call_tx,
+        harvest_tx,
+        pay_fee_tx,
+    } = harvest(
+        algod,
+        investor,
+        project.central_app_id,
+        amount,
+        &project.central_escrow,
+    )
+    .await
+    .map_err(build_error)?;
+
+    Ok(vec![
+        GroupTx::Unsigned(app_call_tx),
+        GroupTx::Signed(harvest_tx),
+        GroupTx::Unsigned(pay_fee_tx),
+    ])
+}
+
+/// A customer paying the project: a single payment to the customer escrow.
+pub async fn pay_txs(
+    algod: &Algod,
+    project: &Project,
+    customer: &Address,
+    amount: MicroAlgos,
+) -> Result<Vec<GroupTx>, ServiceError> {
+    let PayProjectToSign { tx } =
+        pay_project(algod, customer, project.customer_escrow.address(), amount)
+            .await
+            .map_err(build_error)?;
+
+    Ok(vec![GroupTx::Unsigned(tx)])
+}
+
+/// The flows only fail loading the suggested params from algod (or signing, which doesn't fail with valid escrows)
+fn build_error(e: anyhow::Error) -> ServiceError {
+    ServiceError::Upstream(format!("Couldn't build the transactions: {}", e))
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::{harvest_txs, invest_txs, pay_txs, stake_txs, unstake_txs, GroupTx};
+    use crate::test_data::{project_json, stub_algod};
+    use algonaut::{
+        core::{Address, MicroAlgos},
+        transaction::{Transaction, TransactionType},
+    };
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    // The expectations are those of the escrows' programs in test_data (disassemble them with GET /projects/{uuid}/escrows/{kind}).
+
+    #[test]
+    async fn test_invest_txs() -> Result<()> {
+        // 100 shares at 1 algo
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+
+        let txs = as_txs(invest_txs(&stub_algod()?, &project, &investor()?, 10).await?);
+
+        // the invest escrow only approves groups of 6
+        assert_eq!(6, txs.len());
+        assert_same_group(&txs);
+        // the escrow signs the shares and votes transfers
+        assert_eq!(
+            vec![false, false, false, true, true, false],
+            txs.iter().map(|(signed, _)| *signed).collect::<Vec<_>>()
+        );
+        assert_app_call(&txs[0].1, &project);
+        // gtxn 1 Amount == gtxn 3 AssetAmount * price
+        let (_, receiver, amount) = payment(&txs[1].1);
+        assert_eq!(*project.central_escrow.address(), receiver);
+        assert_eq!(MicroAlgos(10_000_000), amount);
+        // gtxn 3: the shares, from the invest escrow to the staking escrow
+        let (sender, asset_id, shares_amount, shares_receiver) = asset_transfer(&txs[3].1);
+        assert_eq!(*project.invest_escrow.address(), sender);
+        assert_eq!(project.shares_asset_id, asset_id);
+        assert_eq!(10, shares_amount);
+        assert_eq!(*project.staking_escrow.address(), shares_receiver);
+        assert!(txs[3].1.fee <= MicroAlgos(1000));
+        // gtxn 4 AssetAmount == gtxn 3 AssetAmount
+        let (_, _, votes_amount, _) = asset_transfer(&txs[4].1);
+        assert_eq!(shares_amount, votes_amount);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_stake_txs() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let investor = investor()?;
+
+        let txs = as_txs(stake_txs(&stub_algod()?, &project, &investor, 10).await?);
+
+        // the staking escrow only receives: its program doesn't run
+        assert_eq!(2, txs.len());
+        assert_same_group(&txs);
+        assert!(txs.iter().all(|(signed, _)| !signed));
+        assert_app_call(&txs[0].1, &project);
+        let (sender, asset_id, amount, receiver) = asset_transfer(&txs[1].1);
+        assert_eq!(investor, sender);
+        assert_eq!(project.shares_asset_id, asset_id);
+        assert_eq!(10, amount);
+        assert_eq!(*project.staking_escrow.address(), receiver);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unstake_txs() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let investor = investor()?;
+
+        let txs = as_txs(unstake_txs(&stub_algod()?, &project, &investor, 10).await?);
+
+        // the staking escrow approves groups of 3: app call, asset transfer, payment
+        assert_eq!(3, txs.len());
+        assert_same_group(&txs);
+        assert_eq!(
+            vec![false, true, false],
+            txs.iter().map(|(signed, _)| *signed).collect::<Vec<_>>()
+        );
+        assert_app_call(&txs[0].1, &project);
+        let (sender, asset_id, amount, receiver) = asset_transfer(&txs[1].1);
+        assert_eq!(*project.staking_escrow.address(), sender);
+        assert_eq!(project.shares_asset_id, asset_id);
+        assert_eq!(10, amount);
+        assert_eq!(investor, receiver);
+        assert!(txs[1].1.fee <= MicroAlgos(1000));
+        let (_, fee_receiver, fee) = payment(&txs[2].1);
+        assert_eq!(*project.staking_escrow.address(), fee_receiver);
+        assert_eq!(txs[1].1.fee, fee);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_harvest_txs() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let investor = investor()?;
+
+        let txs =
+            as_txs(harvest_txs(&stub_algod()?, &project, &investor, MicroAlgos(1_000)).await?);
+
+        // the central escrow leaves the checks to the app: the group must call it
+        assert_eq!(3, txs.len());
+        assert_same_group(&txs);
+        assert_eq!(
+            vec![false, true, false],
+            txs.iter().map(|(signed, _)| *signed).collect::<Vec<_>>()
+        );
+        assert_app_call(&txs[0].1, &project);
+        let (sender, receiver, amount) = payment(&txs[1].1);
+        assert_eq!(*project.central_escrow.address(), sender);
+        assert_eq!(investor, receiver);
+        assert_eq!(MicroAlgos(1_000), amount);
+        let (_, fee_receiver, fee) = payment(&txs[2].1);
+        assert_eq!(*project.central_escrow.address(), fee_receiver);
+        assert_eq!(txs[1].1.fee, fee);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_pay_txs() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let customer = investor()?;
+
+        let txs = as_txs(pay_txs(&stub_algod()?, &project, &customer, MicroAlgos(5_000)).await?);
+
+        // a plain payment: the customer escrow's program only runs when it's drained
+        assert_eq!(1, txs.len());
+        assert!(!txs[0].0);
+        let (sender, receiver, amount) = payment(&txs[0].1);
+        assert_eq!(customer, sender);
+        assert_eq!(*project.customer_escrow.address(), receiver);
+        assert_eq!(MicroAlgos(5_000), amount);
+        Ok(())
+    }
+
+    fn investor() -> Result<Address> {
+        "MKRBTLNZRS3UZZDS5OWPLP7YPHUDNKXFUFN5PNCJ3P2XRG74HNOGY6XOYQ"
+            .parse()
+            .map_err(Error::msg)
+    }
+
+    /// (signed, transaction)
+    fn as_txs(txs: Vec<GroupTx>) -> Vec<(bool, Transaction)> {
+        txs.into_iter()
+            .map(|tx| match tx {
+                GroupTx::Unsigned(tx) => (false, tx),
+                GroupTx::Signed(signed) => (true, signed.transaction),
+            })
+            .collect()
+    }
+
+    fn assert_same_group(txs: &[(bool, Transaction)]) {
+        let group = txs[0].1.group;
+        assert!(group.is_some());
+        assert!(txs.iter().all(|(_, tx)| tx.group == group));
+    }
+
+    fn assert_app_call(tx: &Transaction, project: &Project) {
+        match &tx.txn_type {
+            TransactionType::ApplicationCallTransaction(call) => {
+                assert_eq!(project.central_app_id, call.app_id)
+            }
+            tx_type => panic!("Unexpected transaction type: {:?}", tx_type),
+        }
+    }
+
+    /// (sender, receiver, amount)
+    fn payment(tx: &Transaction) -> (Address, Address, MicroAlgos) {
+        match &tx.txn_type {
+            TransactionType::Payment(payment) => (payment.sender, payment.receiver, payment.amount),
+            tx_type => panic!("Unexpected transaction type: {:?}", tx_type),
+        }
+    }
+
+    /// (sender, asset id, amount, receiver)
+    fn asset_transfer(tx: &Transaction) -> (Address, u64, u64, Address) {
+        match &tx.txn_type {
+            TransactionType::AssetTransferTransaction(xfer) => {
+                (xfer.sender, xfer.xfer, xfer.amount, xfer.receiver)
+            }
+            tx_type => panic!("Unexpected transaction type: {:?}", tx_type),
+        }
+    }
+}
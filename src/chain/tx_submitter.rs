@@ -0,0 +1,76 @@
+use algonaut::algod::v2::Algod;
+use async_trait::async_trait;
+
+use crate::error::ServiceError;
+
+use super::{algod_error_status, is_algod_not_found};
+
+/// Submits signed transactions to the chain and reports on them (algod).
+#[async_trait]
+pub trait TxSubmitter: Sync + Send {
+    /// `signed_txs`: the concatenated msgpack of the signed transactions (a group, in order)
+    ///
+    /// InvalidInput if the node rejected the transactions. With Upstream they may still be confirmed.
+    async fn submit(&self, signed_txs: &[u8]) -> Result<(), ServiceError>;
+    /// The last round the node has seen
+    async fn current_round(&self) -> Result<u64, ServiceError>;
+    async fn pending_status(&self, txid: &str) -> Result<PendingStatus, ServiceError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingStatus {
+    /// Waiting in the node's pool
+    InPool,
+    Confirmed(u64),
+    /// Removed from the pool, with the reason
+    Rejected(String),
+    /// Not known by the node (404). Confirmed transactions are forgotten some rounds after their confirmation,
+    /// so this isn't enough to expire a transaction (see relay).
+    NotFound,
+}
+
+#[async_trait]
+impl TxSubmitter for Algod {
+    async fn submit(&self, signed_txs: &[u8]) -> Result<(), ServiceError> {
+        // algod answers invalid transactions (e.g. failing logic, overspend) with 400
+        match self.broadcast_raw_transaction(signed_txs).await {
+            Ok(_) => Ok(()),
+            Err(e) => match algod_error_status(&e) {
+                Some(status) if (400..500).contains(&status) => Err(ServiceError::InvalidInput(
+                    format!("Transaction rejected: {}", e),
+                )),
+                // didn't answer or failed: the transactions may have reached the pool
+                _ => Err(ServiceError::Upstream(format!(
+                    "Couldn't submit the transactions: {}",
+                    e
+                ))),
+            },
+        }
+    }
+
+    async fn current_round(&self) -> Result<u64, ServiceError> {
+        Ok(self
+            .status()
+            .await
+            .map_err(|e| ServiceError::Upstream(format!("Algod not available: {}", e)))?
+            .last_round)
+    }
+
+    async fn pending_status(&self, txid: &str) -> Result<PendingStatus, ServiceError> {
+        let tx = match self.pending_transaction_with_id(txid).await {
+            Ok(tx) => tx,
+            Err(e) if is_algod_not_found(&e) => return Ok(PendingStatus::NotFound),
+            Err(e) => {
+                return Err(ServiceError::Upstream(format!(
+                    "Couldn't load pending transaction: {}: {}",
+                    txid, e
+                )))
+            }
+        };
+        Ok(match tx.confirmed_round {
+            Some(round) if round > 0 => PendingStatus::Confirmed(round),
+            _ if !tx.pool_error.is_empty() => PendingStatus::Rejected(tx.pool_error),
+            _ => PendingStatus::InPool,
+        })
+    }
+}
@@ -0,0 +1,207 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Deserializer};
+use structopt::StructOpt;
+use url::Url;
+
+use crate::{
+    auth::AuthConfig, chain::ChainConfig, dao::db::DbConfig, images::ImagesConfig,
+    indexing::IndexingConfig, relay::RelayConfig, storage::StorageConfig,
+};
+
+const DEFAULT_CONFIG_FILE: &str = "config/default.toml";
+const ENV_PREFIX: &str = "CAPI";
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "backend")]
+pub struct Opt {
+    /// Config file, merged on top of config/default.toml
+    #[structopt(long, short)]
+    pub config: Option<PathBuf>,
+
+    #[structopt(long)]
+    pub bind_address: Option<SocketAddr>,
+
+    #[structopt(long)]
+    pub frontend_base_url: Option<String>,
+
+    #[structopt(long)]
+    pub log_config_path: Option<String>,
+
+    #[structopt(long)]
+    pub db_host: Option<String>,
+
+    #[structopt(long)]
+    pub db_port: Option<u16>,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Applies the pending database migrations and exits
+    Migrate,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    pub bind_address: SocketAddr,
+    /// Used to build the links to the projects
+    pub frontend_base_url: String,
+    #[serde(deserialize_with = "list_or_comma_separated")]
+    pub cors_origins: Vec<String>,
+    pub log_config_path: String,
+    pub tls: Option<TlsConfig>,
+    pub db: DbConfig,
+    pub chain: ChainConfig,
+    pub auth: AuthConfig,
+    pub storage: StorageConfig,
+    pub images: ImagesConfig,
+    pub indexing: IndexingConfig,
+    pub relay: RelayConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl Config {
+    /// Merges, in increasing priority: the default config file, the config file passed in the options,
+    /// the environment variables and the options.
+    pub fn load(opt: &Opt) -> Result<Config> {
+        let mut settings = config::Config::new();
+        settings
+            .merge(config::File::with_name(DEFAULT_CONFIG_FILE))
+            .with_context(|| format!("Couldn't read config file: {}", DEFAULT_CONFIG_FILE))?;
+        if let Some(path) = &opt.config {
+            settings
+                .merge(config::File::from(path.as_path()))
+                .with_context(|| format!("Couldn't read config file: {:?}", path))?;
+        }
+        settings.merge(config::Environment::with_prefix(ENV_PREFIX).separator("__"))?;
+
+        if let Some(bind_address) = opt.bind_address {
+            settings.set("bind_address", bind_address.to_string())?;
+        }
+        if let Some(frontend_base_url) = &opt.frontend_base_url {
+            settings.set("frontend_base_url", frontend_base_url.as_str())?;
+        }
+        if let Some(log_config_path) = &opt.log_config_path {
+            settings.set("log_config_path", log_config_path.as_str())?;
+        }
+        if let Some(db_host) = &opt.db_host {
+            settings.set("db.host", db_host.as_str())?;
+        }
+        if let Some(db_port) = opt.db_port {
+            settings.set("db.port", db_port as i64)?;
+        }
+
+        let mut config: Config = settings.try_into().context("Invalid config")?;
+        config.validate().context("Invalid config")?;
+        Ok(config)
+    }
+
+    fn validate(&mut self) -> Result<()> {
+        // links are built appending paths
+        self.frontend_base_url = self.frontend_base_url.trim_end_matches('/').to_owned();
+        validate_http_url("frontend_base_url", &self.frontend_base_url)?;
+
+        if self.cors_origins.is_empty() {
+            return Err(anyhow!("cors_origins: at least one origin is required"));
+        }
+        for origin in &self.cors_origins {
+            validate_http_url("cors_origins", origin)?;
+        }
+
+        if let Some(tls) = &self.tls {
+            if !tls.cert_path.is_file() {
+                return Err(anyhow!(
+                    "tls.cert_path: file not found: {:?}",
+                    tls.cert_path
+                ));
+            }
+            if !tls.key_path.is_file() {
+                return Err(anyhow!("tls.key_path: file not found: {:?}", tls.key_path));
+            }
+        }
+
+        if self.db.pool_max_size == 0 {
+            return Err(anyhow!("db.pool_max_size: must be greater than 0"));
+        }
+
+        validate_http_url("chain.algod_url", &self.chain.algod_url)?;
+        validate_http_url("chain.indexer_url", &self.chain.indexer_url)?;
+
+        if self.auth.challenge_ttl_secs <= 0 || self.auth.session_ttl_secs <= 0 {
+            return Err(anyhow!("auth: ttls must be greater than 0"));
+        }
+
+        if let StorageConfig::S3 {
+            endpoint: Some(endpoint),
+            ..
+        } = &self.storage
+        {
+            validate_http_url("storage.endpoint", endpoint)?;
+        }
+        if self.images.max_size_bytes == 0
+            || self.images.max_dimension == 0
+            || self.images.thumbnail_size == 0
+        {
+            return Err(anyhow!("images: limits must be greater than 0"));
+        }
+        if self.indexing.poll_interval_secs == 0 {
+            return Err(anyhow!(
+                "indexing.poll_interval_secs: must be greater than 0"
+            ));
+        }
+        if self.relay.poll_interval_secs == 0 {
+            return Err(anyhow!("relay.poll_interval_secs: must be greater than 0"));
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_http_url(key: &str, value: &str) -> Result<()> {
+    let url = Url::parse(value).with_context(|| format!("{}: invalid url: {}", key, value))?;
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(anyhow!(
+            "{}: unsupported scheme: {} in: {}",
+            key,
+            scheme,
+            value
+        )),
+    }
+}
+
+/// Lists can't be expressed in environment variables, so we accept a comma separated string as well
+fn list_or_comma_separated<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ListOrString {
+        List(Vec<String>),
+        String(String),
+    }
+
+    Ok(match ListOrString::deserialize(deserializer)? {
+        ListOrString::List(list) => list,
+        ListOrString::String(str) => str
+            .split(',')
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+pub fn test_config() -> Result<Config> {
+    Config::load(&Opt::from_iter(vec!["backend"]))
+}
@@ -0,0 +1,104 @@
+use algonaut::core::Address;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+
+use crate::error::ServiceError;
+
+use super::db::{get_address, get_client};
+
+#[async_trait]
+pub trait AuthDao: Sync + Send {
+    async fn save_challenge(
+        &self,
+        nonce: &str,
+        address: &Address,
+        expires_at: &DateTime<Utc>,
+    ) -> Result<(), ServiceError>;
+    /// Deletes the challenge, so it can be used only once.
+    /// Returns false if there's no unexpired challenge with this nonce for the address.
+    async fn take_challenge(&self, nonce: &str, address: &Address) -> Result<bool, ServiceError>;
+
+    async fn save_session(
+        &self,
+        token_hash: &str,
+        address: &Address,
+        expires_at: &DateTime<Utc>,
+    ) -> Result<(), ServiceError>;
+    /// Returns the address of the session, if it exists and hasn't expired
+    async fn load_session(&self, token_hash: &str) -> Result<Option<Address>, ServiceError>;
+}
+
+pub struct AuthDaoImpl {
+    pub pool: Pool,
+}
+
+#[async_trait]
+impl AuthDao for AuthDaoImpl {
+    async fn save_challenge(
+        &self,
+        nonce: &str,
+        address: &Address,
+        expires_at: &DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        // opportunistic cleanup, challenges are short lived
+        client
+            .execute("DELETE FROM auth_challenge WHERE expires_at < now();", &[])
+            .await?;
+        client
+            .execute(
+                "INSERT INTO auth_challenge (nonce, address, expires_at) VALUES ($1, $2, $3);",
+                &[&nonce, &address.to_string(), expires_at],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn take_challenge(&self, nonce: &str, address: &Address) -> Result<bool, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "DELETE FROM auth_challenge WHERE nonce=$1 AND address=$2 RETURNING expires_at > now();",
+                &[&nonce, &address.to_string()],
+            )
+            .await?;
+        Ok(match rows.as_slice() {
+            [row] => row.get(0),
+            _ => false,
+        })
+    }
+
+    async fn save_session(
+        &self,
+        token_hash: &str,
+        address: &Address,
+        expires_at: &DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        client
+            .execute("DELETE FROM session WHERE expires_at < now();", &[])
+            .await?;
+        client
+            .execute(
+                "INSERT INTO session (token_hash, address, expires_at) VALUES ($1, $2, $3);",
+                &[&token_hash, &address.to_string(), expires_at],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_session(&self, token_hash: &str) -> Result<Option<Address>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT address FROM session WHERE token_hash=$1 AND expires_at > now();",
+                &[&token_hash],
+            )
+            .await?;
+        match rows.as_slice() {
+            [row] => Ok(Some(get_address(row, 0)?)),
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use algonaut::core::{Address, MicroAlgos};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::{
+    db::{get_client, get_count, get_u64, to_bigint, to_numeric},
+    indexer_cursor::{load_last_round, row_param, save_indexed_txs, IndexedTxTable},
+    time_series::{BucketInterval, TimeBucket},
+};
+
+const TABLE: IndexedTxTable = IndexedTxTable {
+    name: "customer_payments",
+    columns: &["txid", "payer", "amount", "note", "round", "round_time"],
+};
+
+#[async_trait]
+pub trait CustomerPaymentDao: Sync + Send {
+    /// The last round up to which the customer payments of the project were indexed
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError>;
+    /// Saves the customer payments indexed up to `round`, see indexer_cursor::save_indexed_txs
+    async fn save_customer_payments(
+        &self,
+        project_uuid: &Uuid,
+        payments: &[CustomerPayment],
+        round: u64,
+    ) -> Result<(), ServiceError>;
+    /// The payments in [from, to) summed by interval, oldest first. Intervals without payments are omitted.
+    async fn load_revenue(
+        &self,
+        project_uuid: &Uuid,
+        interval: BucketInterval,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TimeBucket>, ServiceError>;
+    /// The sum of all the payments (microalgos)
+    async fn load_total_revenue(&self, project_uuid: &Uuid) -> Result<u64, ServiceError>;
+}
+
+pub struct CustomerPaymentDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomerPayment {
+    pub txid: String,
+    pub payer: Address,
+    pub amount: MicroAlgos,
+    pub note: Option<Vec<u8>>,
+    pub round: u64,
+    pub round_time: DateTime<Utc>,
+}
+
+#[async_trait]
+impl CustomerPaymentDao for CustomerPaymentDaoImpl {
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        load_last_round(&client, project_uuid, TABLE.name).await
+    }
+
+    async fn save_customer_payments(
+        &self,
+        project_uuid: &Uuid,
+        payments: &[CustomerPayment],
+        round: u64,
+    ) -> Result<(), ServiceError> {
+        save_indexed_txs(
+            &self.pool,
+            &TABLE,
+            project_uuid,
+            payments,
+            round,
+            |payment| {
+                Ok(vec![
+                    row_param(payment.txid.clone()),
+                    row_param(payment.payer.to_string()),
+                    row_param(to_numeric(payment.amount.0)),
+                    row_param(payment.note.clone()),
+                    row_param(to_bigint(payment.round)?),
+                    row_param(payment.round_time),
+                ])
+            },
+        )
+        .await
+    }
+
+    async fn load_revenue(
+        &self,
+        project_uuid: &Uuid,
+        interval: BucketInterval,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Vec<TimeBucket>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT date_trunc($2, cp.round_time AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS bucket, SUM(cp.amount), COUNT(*)
+                FROM customer_payments cp JOIN project p ON p.id = cp.project_id
+                WHERE p.uuid=$1::TEXT::UUID
+                    AND ($3::TIMESTAMPTZ IS NULL OR cp.round_time >= $3)
+                    AND ($4::TIMESTAMPTZ IS NULL OR cp.round_time < $4)
+                GROUP BY bucket ORDER BY bucket;",
+                &[&project_uuid.to_string(), &interval.as_str(), &from, &to],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                Ok(TimeBucket {
+                    start: row.get(0),
+                    amount: get_u64(row, 1)?,
+                    count: get_count(row, 2)?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?)
+    }
+
+    async fn load_total_revenue(&self, project_uuid: &Uuid) -> Result<u64, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let row = client
+            .query_one(
+                "SELECT COALESCE(SUM(cp.amount), 0) FROM customer_payments cp JOIN project p ON p.id = cp.project_id
+                WHERE p.uuid=$1::TEXT::UUID;",
+                &[&project_uuid.to_string()],
+            )
+            .await?;
+        Ok(get_u64(&row, 0)?)
+    }
+}
@@ -1,32 +1,97 @@
+use std::{convert::TryFrom, time::Duration};
+
 use algonaut::core::{Address, CompiledTeal, MicroAlgos};
-use anyhow::{Error, Result};
-use data_encoding::BASE64;
-use tokio_postgres::{Client, NoTls, Row};
-
-pub async fn create_db_client() -> Result<Client> {
-    // Connect to the database.
-    let (client, connection) =
-        tokio_postgres::connect("host=localhost user=postgres password=postgres", NoTls).await?;
-
-    // The connection object performs the actual communication with the database,
-    // so spawn it off to run on its own.
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("connection error: {}", e);
-        }
+use anyhow::{anyhow, Error, Result};
+use deadpool_postgres::{
+    ManagerConfig, Object, Pool, PoolConfig, PoolError, RecyclingMethod, Runtime, Timeouts,
+};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
+use tokio_postgres::{NoTls, Row};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DbConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub dbname: String,
+    pub pool_max_size: usize,
+    pub pool_wait_timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+}
+
+/// Creates the connection pool. Connections are opened lazily.
+/// Closed connections (e.g. after the database restarted) are discarded and replaced when handed out,
+/// and idle connections are verified with a query before being reused.
+pub fn create_db_pool(config: &DbConfig) -> Result<Pool> {
+    let connect_timeout = Duration::from_secs(config.connect_timeout_secs);
+
+    let mut pool_config = deadpool_postgres::Config::new();
+    pool_config.host = Some(config.host.clone());
+    pool_config.port = Some(config.port);
+    pool_config.user = Some(config.user.clone());
+    pool_config.password = Some(config.password.clone());
+    pool_config.dbname = Some(config.dbname.clone());
+    pool_config.connect_timeout = Some(connect_timeout);
+    pool_config.manager = Some(ManagerConfig {
+        recycling_method: RecyclingMethod::Verified,
+    });
+    pool_config.pool = Some(PoolConfig {
+        max_size: config.pool_max_size,
+        timeouts: Timeouts {
+            wait: Some(Duration::from_secs(config.pool_wait_timeout_secs)),
+            create: Some(connect_timeout),
+            recycle: Some(connect_timeout),
+        },
     });
 
-    Ok(client)
+    Ok(pool_config.create_pool(Some(Runtime::Tokio1), NoTls)?)
 }
 
+/// Gets a connection from the pool, logging when the pool is exhausted or the database unreachable.
+pub async fn get_client(pool: &Pool) -> Result<Object, PoolError> {
+    pool.get().await.map_err(|e| {
+        if let PoolError::Timeout(timeout_type) = &e {
+            let status = pool.status();
+            log::warn!(
+                "DB pool timeout ({:?}): size: {}, max size: {}, available: {}",
+                timeout_type,
+                status.size,
+                status.max_size,
+                status.available
+            );
+        }
+        e
+    })
+}
+
+/// Reads a NUMERIC column holding an u64 (amounts and counts, which don't fit in BIGINT).
 pub fn get_u64(row: &Row, index: usize) -> Result<u64> {
-    row.get::<_, String>(index).parse().map_err(Error::msg)
+    let value: Decimal = row.get(index);
+    value
+        .to_u64()
+        .ok_or_else(|| anyhow!("Invalid u64 in column {}: {}", index, value))
+}
+
+/// Reads a BIGINT column holding an u64 (chain ids).
+pub fn get_id(row: &Row, index: usize) -> Result<u64> {
+    Ok(u64::try_from(row.get::<_, i64>(index))?)
+}
+
+/// Reads a COUNT(*) column
+pub fn get_count(row: &Row, index: usize) -> Result<u64> {
+    Ok(u64::try_from(row.get::<_, i64>(index))?)
+}
+
+/// Reads a SMALLINT column holding an u64 (small values, like percentages).
+pub fn get_small_u64(row: &Row, index: usize) -> Result<u64> {
+    Ok(u64::try_from(row.get::<_, i16>(index))?)
 }
 
 pub fn get_microalgos(row: &Row, index: usize) -> Result<MicroAlgos> {
-    Ok(MicroAlgos(
-        row.get::<_, String>(index).parse().map_err(Error::msg)?,
-    ))
+    Ok(MicroAlgos(get_u64(row, index)?))
 }
 
 pub fn get_address(row: &Row, index: usize) -> Result<Address> {
@@ -34,7 +99,31 @@ pub fn get_address(row: &Row, index: usize) -> Result<Address> {
 }
 
 pub fn get_bytes(row: &Row, index: usize) -> Result<CompiledTeal> {
-    Ok(CompiledTeal(
-        BASE64.decode(row.get::<_, String>(index).as_bytes())?,
-    ))
+    Ok(CompiledTeal(row.get(index)))
+}
+
+/// Reads a UUID column. It has to be selected as text (`uuid::TEXT`),
+/// as tokio-postgres doesn't support the uuid version we use.
+pub fn get_uuid(row: &Row, index: usize) -> Result<Uuid> {
+    Ok(row.get::<_, String>(index).parse()?)
+}
+
+/// Like get_uuid, for nullable columns
+pub fn get_optional_uuid(row: &Row, index: usize) -> Result<Option<Uuid>> {
+    Ok(row
+        .get::<_, Option<String>>(index)
+        .map(|uuid| uuid.parse())
+        .transpose()?)
+}
+
+pub fn to_numeric(value: u64) -> Decimal {
+    Decimal::from(value)
+}
+
+pub fn to_bigint(value: u64) -> Result<i64> {
+    Ok(i64::try_from(value)?)
+}
+
+pub fn to_smallint(value: u64) -> Result<i16> {
+    Ok(i16::try_from(value)?)
 }
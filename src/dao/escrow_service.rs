@@ -0,0 +1,64 @@
+use algonaut::transaction::contract_account::ContractAccount;
+use data_encoding::{BASE64, HEXLOWER};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{error::ServiceError, teal::disassemble};
+
+use super::{project_dao::ProjectDao, project_service::parse_uuid};
+
+/// What an escrow of the project runs, to be inspected by investors and auditors
+#[derive(Debug, Serialize)]
+pub struct EscrowJson {
+    pub kind: String,
+    pub address: String,
+    /// Hex SHA-256 of the program, to compare with a build of the contract's source
+    pub program_hash: String,
+    /// Base64
+    pub program: String,
+    /// None if the program couldn't be disassembled (see error)
+    pub teal_version: Option<u64>,
+    /// Disassembled program
+    pub teal: Option<String>,
+    /// Why the program couldn't be disassembled (e.g. opcodes we don't support)
+    pub error: Option<String>,
+}
+
+/// `kind`: invest, staking, central or customer
+pub async fn load_escrow(
+    project_dao: &dyn ProjectDao,
+    uuid: &str,
+    kind: &str,
+) -> Result<EscrowJson, ServiceError> {
+    let uuid = parse_uuid(uuid)?;
+    let project = project_dao.load_project(&uuid).await?.project;
+    let escrow = match kind {
+        "invest" => &project.invest_escrow,
+        "staking" => &project.staking_escrow,
+        "central" => &project.central_escrow,
+        "customer" => &project.customer_escrow,
+        _ => return Err(ServiceError::NotFound(format!("Unknown escrow: {}", kind))),
+    };
+    Ok(to_escrow_json(kind, escrow))
+}
+
+fn to_escrow_json(kind: &str, escrow: &ContractAccount) -> EscrowJson {
+    let program = &escrow.program.0;
+    let (teal_version, teal, error) = match disassemble(program) {
+        Ok(disassembly) => (
+            Some(disassembly.version),
+            Some(disassembly.lines.join("\n")),
+            None,
+        ),
+        Err(e) => (None, None, Some(e.to_string())),
+    };
+    EscrowJson {
+        kind: kind.to_owned(),
+        address: escrow.address().to_string(),
+        program_hash: HEXLOWER.encode(&Sha256::digest(program)),
+        program: BASE64.encode(program),
+        teal_version,
+        teal,
+        error,
+    }
+}
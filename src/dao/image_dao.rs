@@ -0,0 +1,82 @@
+use std::convert::TryFrom;
+
+use algonaut::core::Address;
+use anyhow::Error;
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::db::{get_address, get_client, get_uuid};
+
+#[async_trait]
+pub trait ImageDao: Sync + Send {
+    async fn save_image(&self, image: &Image) -> Result<(), ServiceError>;
+    async fn load_image(&self, id: &Uuid) -> Result<Image, ServiceError>;
+}
+
+pub struct ImageDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub id: Uuid,
+    pub uploader: Address,
+    pub content_type: String,
+    pub width: u32,
+    pub height: u32,
+    pub size_bytes: u32,
+}
+
+#[async_trait]
+impl ImageDao for ImageDaoImpl {
+    async fn save_image(&self, image: &Image) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO image (id, uploader, content_type, width, height, size_bytes) VALUES ($1::TEXT::UUID, $2, $3, $4, $5, $6);",
+                &[
+                    &image.id.to_string(),
+                    &image.uploader.to_string(),
+                    &image.content_type,
+                    &to_integer(image.width)?,
+                    &to_integer(image.height)?,
+                    &to_integer(image.size_bytes)?,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_image(&self, id: &Uuid) -> Result<Image, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT id::TEXT, uploader, content_type, width, height, size_bytes FROM image WHERE id=$1::TEXT::UUID;",
+                &[&id.to_string()],
+            )
+            .await?;
+
+        match rows.as_slice() {
+            [row] => Ok(Image {
+                id: get_uuid(row, 0)?,
+                uploader: get_address(row, 1)?,
+                content_type: row.get(2),
+                width: from_integer(row.get(3))?,
+                height: from_integer(row.get(4))?,
+                size_bytes: from_integer(row.get(5))?,
+            }),
+            _ => Err(ServiceError::NotFound(format!("Image not found: {}", id))),
+        }
+    }
+}
+
+fn to_integer(value: u32) -> Result<i32, ServiceError> {
+    Ok(i32::try_from(value).map_err(Error::from)?)
+}
+
+fn from_integer(value: i32) -> Result<u32, ServiceError> {
+    Ok(u32::try_from(value).map_err(Error::from)?)
+}
@@ -0,0 +1,131 @@
+use deadpool_postgres::Pool;
+use tokio_postgres::{types::ToSql, Client, Transaction};
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::db::{get_client, get_id, to_bigint};
+
+/// The params of an indexed transaction's row
+pub type RowParams = Vec<Box<dyn ToSql + Sync + Send>>;
+
+pub fn row_param<P: ToSql + Sync + Send + 'static>(value: P) -> Box<dyn ToSql + Sync + Send> {
+    Box::new(value)
+}
+
+/// A table of transactions indexed per project, with a unique txid.
+pub struct IndexedTxTable {
+    /// Also the kind of the cursor
+    pub name: &'static str,
+    /// The columns after project_id, starting with txid
+    pub columns: &'static [&'static str],
+}
+
+impl IndexedTxTable {
+    fn insert_sql(&self) -> String {
+        let params: Vec<String> = (0..self.columns.len())
+            .map(|i| format!("${}", i + 2))
+            .collect();
+        format!(
+            "INSERT INTO {} (project_id, {})
+            SELECT id, {} FROM project WHERE uuid=$1::TEXT::UUID
+            ON CONFLICT (txid) DO NOTHING;",
+            self.name,
+            self.columns.join(", "),
+            params.join(", ")
+        )
+    }
+}
+
+/// Saves the transactions indexed up to `round` (inclusive) and advances the project's cursor to it, atomically.
+/// Transactions that were already saved are ignored: a round range is indexed again if saving failed.
+/// `to_params`: the values of `table.columns`, in the same order.
+pub async fn save_indexed_txs<T, F>(
+    pool: &Pool,
+    table: &IndexedTxTable,
+    project_uuid: &Uuid,
+    txs: &[T],
+    round: u64,
+    to_params: F,
+) -> Result<(), ServiceError>
+where
+    F: Fn(&T) -> Result<RowParams, ServiceError>,
+{
+    let sql = table.insert_sql();
+    let uuid = project_uuid.to_string();
+    let mut client = get_client(pool).await?;
+    let tx = client.transaction().await?;
+    for indexed_tx in txs {
+        let row_params = to_params(indexed_tx)?;
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![&uuid];
+        params.extend(row_params.iter().map(|p| p.as_ref() as &(dyn ToSql + Sync)));
+        tx.execute(sql.as_str(), &params).await?;
+    }
+    save_last_round(&tx, project_uuid, table.name, round).await?;
+    tx.commit().await?;
+
+    log::debug!(
+        "Saved {} {} for project: {}, up to round: {}",
+        txs.len(),
+        table.name,
+        project_uuid,
+        round
+    );
+    Ok(())
+}
+
+/// The last round processed by the indexer `kind` for the project, None if it hasn't run yet
+pub async fn load_last_round(
+    client: &Client,
+    project_uuid: &Uuid,
+    kind: &str,
+) -> Result<Option<u64>, ServiceError> {
+    let rows = client
+        .query(
+            "SELECT c.last_round FROM indexer_cursor c JOIN project p ON p.id = c.project_id
+            WHERE p.uuid=$1::TEXT::UUID AND c.kind=$2;",
+            &[&project_uuid.to_string(), &kind],
+        )
+        .await?;
+    match rows.as_slice() {
+        [row] => Ok(Some(get_id(row, 0)?)),
+        _ => Ok(None),
+    }
+}
+
+/// To be called in the transaction that saves what was indexed up to `round`
+pub async fn save_last_round(
+    tx: &Transaction<'_>,
+    project_uuid: &Uuid,
+    kind: &str,
+    round: u64,
+) -> Result<(), ServiceError> {
+    tx.execute(
+        "INSERT INTO indexer_cursor (project_id, kind, last_round)
+        SELECT id, $2, $3 FROM project WHERE uuid=$1::TEXT::UUID
+        ON CONFLICT (project_id, kind) DO UPDATE SET last_round=excluded.last_round, updated_at=now();",
+        &[&project_uuid.to_string(), &kind, &to_bigint(round)?],
+    )
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::IndexedTxTable;
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_insert_sql_numbers_the_params_after_the_project() -> Result<()> {
+        let table = IndexedTxTable {
+            name: "withdrawals",
+            columns: &["txid", "amount"],
+        };
+        let sql = table.insert_sql();
+        assert!(sql.starts_with("INSERT INTO withdrawals (project_id, txid, amount)"));
+        assert!(sql.contains("SELECT id, $2, $3 FROM project WHERE uuid=$1::TEXT::UUID"));
+        assert!(sql.ends_with("ON CONFLICT (txid) DO NOTHING;"));
+        Ok(())
+    }
+}
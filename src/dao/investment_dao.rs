@@ -0,0 +1,79 @@
+use algonaut::core::{Address, MicroAlgos};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::{
+    db::{get_client, to_bigint, to_numeric},
+    indexer_cursor::{load_last_round, row_param, save_indexed_txs, IndexedTxTable},
+};
+
+const TABLE: IndexedTxTable = IndexedTxTable {
+    name: "investments",
+    columns: &["txid", "investor", "shares", "paid", "round", "round_time"],
+};
+
+#[async_trait]
+pub trait InvestmentDao: Sync + Send {
+    /// The last round up to which the investments of the project were indexed
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError>;
+    /// Saves the investments indexed up to `round`, see indexer_cursor::save_indexed_txs
+    async fn save_investments(
+        &self,
+        project_uuid: &Uuid,
+        investments: &[Investment],
+        round: u64,
+    ) -> Result<(), ServiceError>;
+}
+
+pub struct InvestmentDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Investment {
+    /// Of the shares transfer
+    pub txid: String,
+    pub investor: Address,
+    pub shares: u64,
+    pub paid: MicroAlgos,
+    pub round: u64,
+    pub round_time: DateTime<Utc>,
+}
+
+#[async_trait]
+impl InvestmentDao for InvestmentDaoImpl {
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        load_last_round(&client, project_uuid, TABLE.name).await
+    }
+
+    async fn save_investments(
+        &self,
+        project_uuid: &Uuid,
+        investments: &[Investment],
+        round: u64,
+    ) -> Result<(), ServiceError> {
+        save_indexed_txs(
+            &self.pool,
+            &TABLE,
+            project_uuid,
+            investments,
+            round,
+            |investment| {
+                Ok(vec![
+                    row_param(investment.txid.clone()),
+                    row_param(investment.investor.to_string()),
+                    row_param(to_numeric(investment.shares)),
+                    row_param(to_numeric(investment.paid.0)),
+                    row_param(to_bigint(investment.round)?),
+                    row_param(investment.round_time),
+                ])
+            },
+        )
+        .await
+    }
+}
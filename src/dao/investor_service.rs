@@ -0,0 +1,135 @@
+use std::convert::TryFrom;
+
+use algonaut::core::Address;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{chain::indexer::ChainIndexer, error::ServiceError};
+
+use super::{
+    customer_payment_dao::CustomerPaymentDao,
+    project_dao::ProjectDao,
+    project_service::parse_uuid,
+    staking_dao::{InvestorEvent, InvestorEventKind, StakingDao},
+};
+
+/// An investor's position in a project ("my investment")
+#[derive(Debug, Serialize)]
+pub struct InvestorProjectJson {
+    pub investor: String,
+    pub project_uuid: String,
+    /// In the investor's account (not staked)
+    pub shares_held: u64,
+    pub shares_staked: u64,
+    /// Microalgos
+    pub total_harvested: u64,
+    /// Microalgos
+    pub claimable: u64,
+    /// Newest first
+    pub history: Vec<InvestorEventJson>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvestorEventJson {
+    pub txid: String,
+    pub kind: InvestorEventKind,
+    /// Shares, or microalgos for harvests
+    pub amount: u64,
+    pub round: u64,
+    pub round_time: DateTime<Utc>,
+}
+
+impl From<InvestorEvent> for InvestorEventJson {
+    fn from(event: InvestorEvent) -> Self {
+        InvestorEventJson {
+            txid: event.txid,
+            kind: event.kind,
+            amount: event.amount,
+            round: event.round,
+            round_time: event.round_time,
+        }
+    }
+}
+
+/// Staked shares, harvests and revenue are the indexed ones: they can lag behind the chain by a poll interval.
+pub async fn load_investor_project(
+    project_dao: &dyn ProjectDao,
+    staking_dao: &dyn StakingDao,
+    customer_payment_dao: &dyn CustomerPaymentDao,
+    indexer: &dyn ChainIndexer,
+    address: &str,
+    uuid: &str,
+) -> Result<InvestorProjectJson, ServiceError> {
+    let investor: Address = address
+        .parse()
+        .map_err(|_| ServiceError::InvalidInput(format!("Invalid address: {}", address)))?;
+    let uuid = parse_uuid(uuid)?;
+    let project = project_dao.load_project(&uuid).await?.project;
+
+    let totals = staking_dao.load_investor_totals(&uuid, &investor).await?;
+    let shares_staked =
+        (totals.invested_shares + totals.staked_shares).saturating_sub(totals.unstaked_shares);
+    let total_revenue = customer_payment_dao.load_total_revenue(&uuid).await?;
+    let shares_held = indexer
+        .asset_amount(&investor.to_string(), project.shares_asset_id)
+        .await?;
+    let history = staking_dao.load_investor_history(&uuid, &investor).await?;
+
+    Ok(InvestorProjectJson {
+        investor: investor.to_string(),
+        project_uuid: uuid.to_string(),
+        shares_held,
+        shares_staked,
+        total_harvested: totals.harvested,
+        claimable: claimable(
+            total_revenue,
+            project.specs.investors_share,
+            project.specs.shares.count,
+            shares_staked,
+            totals.harvested,
+        ),
+        history: history.into_iter().map(|e| e.into()).collect(),
+    })
+}
+
+/// The investors get `investors_share` percent of the revenue, proportionally to their staked shares.
+/// What was already harvested is deducted.
+fn claimable(
+    total_revenue: u64,
+    investors_share: u64,
+    share_count: u64,
+    staked_shares: u64,
+    harvested: u64,
+) -> u64 {
+    if share_count == 0 {
+        return 0;
+    }
+    let entitled =
+        u128::from(total_revenue) * u128::from(investors_share) * u128::from(staked_shares)
+            / (100 * u128::from(share_count));
+    u64::try_from(entitled)
+        .unwrap_or(u64::MAX)
+        .saturating_sub(harvested)
+}
+
+#[cfg(test)]
+mod test {
+    use super::claimable;
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_claimable() -> Result<()> {
+        // 40% of 1000 algos for the investors, 10% of the shares staked
+        assert_eq!(40_000_000, claimable(1_000_000_000, 40, 100, 10, 0));
+        // partially harvested
+        assert_eq!(
+            15_000_000,
+            claimable(1_000_000_000, 40, 100, 10, 25_000_000)
+        );
+        // harvested before unstaking part of the shares
+        assert_eq!(0, claimable(1_000_000_000, 40, 100, 5, 30_000_000));
+        assert_eq!(0, claimable(1_000_000_000, 40, 0, 0, 0));
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use anyhow::{anyhow, Result};
+use data_encoding::HEXLOWER;
+use sha2::{Digest, Sha256};
+use tokio_postgres::Client;
+
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+// Append only: the checksums of applied migrations are verified on every start,
+// so a migration must never be edited once it has been deployed.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_project",
+        sql: include_str!("../../migrations/0001_create_project.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "native_project_types",
+        sql: include_str!("../../migrations/0002_native_project_types.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "auth",
+        sql: include_str!("../../migrations/0003_auth.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "project_archive",
+        sql: include_str!("../../migrations/0004_project_archive.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "project_listing",
+        sql: include_str!("../../migrations/0005_project_listing.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "project_search",
+        sql: include_str!("../../migrations/0006_project_search.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "project_slug",
+        sql: include_str!("../../migrations/0007_project_slug.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "project_metadata",
+        sql: include_str!("../../migrations/0008_project_metadata.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "images",
+        sql: include_str!("../../migrations/0009_images.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "investments",
+        sql: include_str!("../../migrations/0010_investments.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "customer_payments",
+        sql: include_str!("../../migrations/0011_customer_payments.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "withdrawals",
+        sql: include_str!("../../migrations/0012_withdrawals.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "staking_events",
+        sql: include_str!("../../migrations/0013_staking_events.sql"),
+    },
+    Migration {
+        version: 14,
+        name: "share_holders",
+        sql: include_str!("../../migrations/0014_share_holders.sql"),
+    },
+    Migration {
+        version: 15,
+        name: "project_stats",
+        sql: include_str!("../../migrations/0015_project_stats.sql"),
+    },
+    Migration {
+        version: 16,
+        name: "submitted_txs",
+        sql: include_str!("../../migrations/0016_submitted_txs.sql"),
+    },
+];
+
+// Arbitrary key for the advisory lock that serializes migration runs (e.g. several instances starting at once).
+const LOCK_KEY: i64 = 7_140_221_305;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AppliedMigration {
+    version: i32,
+    name: String,
+    checksum: String,
+}
+
+/// Applies the pending migrations, in order, each in its own transaction.
+/// Fails if the database contains migrations this binary doesn't know (i.e. the database is ahead)
+/// or if an applied migration was modified.
+pub async fn migrate(client: &mut Client) -> Result<()> {
+    client
+        .batch_execute(
+            "CREATE TABLE IF NOT EXISTS schema_migrations(
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+        );",
+        )
+        .await?;
+
+    client
+        .execute("SELECT pg_advisory_lock($1);", &[&LOCK_KEY])
+        .await?;
+    let res = apply_pending(client, MIGRATIONS).await;
+    client
+        .execute("SELECT pg_advisory_unlock($1);", &[&LOCK_KEY])
+        .await?;
+
+    res
+}
+
+async fn apply_pending(client: &mut Client, migrations: &[Migration]) -> Result<()> {
+    let applied = load_applied(client).await?;
+    let pending = pending_migrations(&applied, migrations)?;
+
+    if pending.is_empty() {
+        log::info!(
+            "Database schema is up to date (version: {})",
+            applied.last().map(|m| m.version).unwrap_or(0)
+        );
+    }
+
+    for migration in pending {
+        log::info!(
+            "Applying migration {}: {}",
+            migration.version,
+            migration.name
+        );
+        let tx = client.transaction().await?;
+        tx.batch_execute(migration.sql).await?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version, name, checksum) VALUES ($1, $2, $3);",
+            &[
+                &migration.version,
+                &migration.name,
+                &checksum(migration.sql),
+            ],
+        )
+        .await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+async fn load_applied(client: &Client) -> Result<Vec<AppliedMigration>> {
+    let rows = client
+        .query(
+            "SELECT version, name, checksum FROM schema_migrations ORDER BY version;",
+            &[],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get(0),
+            name: row.get(1),
+            checksum: row.get(2),
+        })
+        .collect())
+}
+
+/// Verifies the applied migrations against the ones known by this binary and returns the ones to apply.
+/// `applied` is expected to be sorted by version.
+fn pending_migrations<'a>(
+    applied: &[AppliedMigration],
+    migrations: &'a [Migration],
+) -> Result<Vec<&'a Migration>> {
+    let latest_known = migrations.last().map(|m| m.version).unwrap_or(0);
+
+    for applied_migration in applied {
+        let migration = migrations
+            .iter()
+            .find(|m| m.version == applied_migration.version)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Database has migration {} ({}), but this binary only knows migrations up to {}. Refusing to start with a database that is ahead of the binary.",
+                    applied_migration.version,
+                    applied_migration.name,
+                    latest_known
+                )
+            })?;
+
+        if checksum(migration.sql) != applied_migration.checksum {
+            return Err(anyhow!(
+                "Checksum mismatch for applied migration {} ({}): the migration was modified after being applied.",
+                migration.version,
+                migration.name
+            ));
+        }
+    }
+
+    let latest_applied = applied.last().map(|m| m.version).unwrap_or(0);
+    let pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .collect();
+
+    if let Some(out_of_order) = pending.iter().find(|m| m.version < latest_applied) {
+        return Err(anyhow!(
+            "Migration {} ({}) is older than the latest applied migration ({}) and can't be applied out of order.",
+            out_of_order.version,
+            out_of_order.name,
+            latest_applied
+        ));
+    }
+
+    Ok(pending)
+}
+
+fn checksum(sql: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(sql.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{checksum, migrate, pending_migrations, AppliedMigration, Migration};
+    use crate::{
+        config::test_config,
+        dao::db::{create_db_pool, get_client},
+        logger::init_logger,
+    };
+    use anyhow::Result;
+    use tokio::test;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "first",
+            sql: "CREATE TABLE foo(id SERIAL PRIMARY KEY);",
+        },
+        Migration {
+            version: 2,
+            name: "second",
+            sql: "ALTER TABLE foo ADD COLUMN bar TEXT;",
+        },
+    ];
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            name: migration.name.to_owned(),
+            checksum: checksum(migration.sql),
+        }
+    }
+
+    #[test]
+    async fn test_pending_migrations_on_partially_migrated_db() -> Result<()> {
+        let pending = pending_migrations(&[applied(&MIGRATIONS[0])], MIGRATIONS)?;
+        assert_eq!(
+            vec![2],
+            pending.iter().map(|m| m.version).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    async fn test_refuses_db_ahead_of_binary() -> Result<()> {
+        let applied = [
+            applied(&MIGRATIONS[0]),
+            applied(&MIGRATIONS[1]),
+            AppliedMigration {
+                version: 3,
+                name: "third".to_owned(),
+                checksum: checksum("DROP TABLE foo;"),
+            },
+        ];
+        assert!(pending_migrations(&applied, MIGRATIONS).is_err());
+        Ok(())
+    }
+
+    #[test]
+    async fn test_refuses_modified_migration() -> Result<()> {
+        let mut modified = applied(&MIGRATIONS[0]);
+        modified.checksum = checksum("CREATE TABLE foo(id INTEGER);");
+        assert!(pending_migrations(&[modified], MIGRATIONS).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    async fn test_migrate() -> Result<()> {
+        init_logger(&test_config()?.log_config_path);
+        let pool = create_db_pool(&test_config()?.db)?;
+        let mut client = get_client(&pool).await?;
+
+        migrate(&mut client).await?;
+        Ok(())
+    }
+}
@@ -1,3 +1,24 @@
+pub mod auth_dao;
+pub mod customer_payment_dao;
 pub mod db;
+pub mod escrow_service;
+pub mod image_dao;
+pub mod indexer_cursor;
+pub mod investment_dao;
+pub mod investor_service;
+pub mod migrations;
 pub mod project_dao;
+pub mod project_metadata_dao;
 pub mod project_service;
+pub mod project_stats_dao;
+pub mod project_stats_service;
+pub mod revenue_service;
+pub mod share_holder_dao;
+pub mod share_holder_service;
+pub mod staking_dao;
+pub mod submission_service;
+pub mod submitted_tx_dao;
+pub mod time_series;
+pub mod transaction_service;
+pub mod withdrawal_dao;
+pub mod withdrawal_service;
@@ -1,203 +1,542 @@
-use std::sync::Arc;
+use std::{collections::HashSet, convert::TryFrom};
 
-use algonaut::transaction::contract_account::ContractAccount;
-use anyhow::{anyhow, Result};
+use algonaut::{core::Address, transaction::contract_account::ContractAccount};
+use anyhow::anyhow;
 use async_trait::async_trait;
 use core_::flows::create_project::model::{CreateProjectSpecs, CreateSharesSpecs, Project};
-use data_encoding::BASE64;
-use tokio_postgres::Client;
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::{types::ToSql, Row};
 use uuid::Uuid;
 
-use super::db::{get_address, get_bytes, get_microalgos, get_u64};
+use crate::{
+    error::ServiceError,
+    search::{HIGHLIGHT_START, HIGHLIGHT_STOP},
+};
+
+use super::db::{
+    get_address, get_bytes, get_client, get_id, get_microalgos, get_small_u64, get_u64, get_uuid,
+    to_bigint, to_numeric, to_smallint,
+};
 
 #[async_trait]
 pub trait ProjectDao: Sync + Send {
-    async fn init(&self) -> Result<()>;
-
-    async fn save_project(&self, project: &Project) -> Result<String>;
-    async fn load_project(&self, id: i32) -> Result<Project>;
-    async fn load_project_with_uuid(&self, uuid: &Uuid) -> Result<Project>;
+    async fn save_project(&self, project: &Project, slug: &str) -> Result<(), ServiceError>;
+    async fn load_project(&self, uuid: &Uuid) -> Result<StoredProject, ServiceError>;
+    /// Also finds projects by their previous slugs: compare with the returned slug.
+    async fn load_project_with_slug(&self, slug: &str) -> Result<StoredProject, ServiceError>;
+    /// Maps the ids used in the old links to the uuids
+    async fn load_legacy_project_uuid(&self, id: i32) -> Result<Uuid, ServiceError>;
+    /// Only the off-chain fields can be updated. The previous slug keeps resolving to the project.
+    /// Conflict if the slug is (or was) another project's.
+    async fn update_project_name(
+        &self,
+        uuid: &Uuid,
+        name: &str,
+        slug: &str,
+    ) -> Result<(), ServiceError>;
+    /// The slugs that are `base` or start with "`base`-", current or previous,
+    /// excluding those of the project with uuid `except`.
+    async fn load_taken_slugs(
+        &self,
+        base: &str,
+        except: Option<&Uuid>,
+    ) -> Result<HashSet<String>, ServiceError>;
+    /// Soft delete: archived projects aren't loaded anymore
+    async fn archive_project(&self, uuid: &Uuid) -> Result<(), ServiceError>;
+    async fn list_projects(&self, query: &ProjectQuery) -> Result<ProjectPage, ServiceError>;
+    /// Not paginated: for background tasks that process every project (e.g. indexing)
+    async fn load_all_projects(&self) -> Result<Vec<StoredProject>, ServiceError>;
+    /// Best matches first. `tsquery` is passed to `to_tsquery`, the terms can match the name or the description.
+    async fn search_projects(
+        &self,
+        tsquery: &str,
+        limit: usize,
+    ) -> Result<Vec<ProjectSearchHit>, ServiceError>;
 }
 pub struct ProjectDaoImpl {
-    pub client: Arc<Client>,
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSort {
+    CreatedAt,
+    Name,
+    AssetPrice,
+}
+
+impl ProjectSort {
+    /// Column and its type, to convert the cursor value back
+    fn column(&self) -> (&'static str, &'static str) {
+        match self {
+            ProjectSort::CreatedAt => ("created_at", "TIMESTAMPTZ"),
+            ProjectSort::Name => ("name", "TEXT"),
+            ProjectSort::AssetPrice => ("asset_price", "NUMERIC"),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Position after which the next page starts: the sort column (as text) and id of the last listed project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectCursor {
+    pub value: String,
+    pub id: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectQuery {
+    pub sort: ProjectSort,
+    pub order: SortOrder,
+    pub limit: usize,
+    pub after: Option<ProjectCursor>,
+    pub creator: Option<Address>,
+    pub token_name: Option<String>,
+}
+
+/// A project with what the database stores besides the project itself.
+#[derive(Debug, Clone)]
+pub struct StoredProject {
+    pub project: Project,
+    /// Current slug
+    pub slug: String,
+}
+
+#[derive(Debug)]
+pub struct ProjectSearchHit {
+    pub project: StoredProject,
+    /// The name, with the matches delimited by search::HIGHLIGHT_START and search::HIGHLIGHT_STOP
+    pub name_highlight: String,
+    /// Fragments of the description around the matches, delimited the same way. None without description.
+    pub description_highlight: Option<String>,
+    pub rank: f32,
+}
+
+#[derive(Debug)]
+pub struct ProjectPage {
+    pub projects: Vec<StoredProject>,
+    /// None if this is the last page
+    pub next_cursor: Option<ProjectCursor>,
+}
+
+// uuid as text: see db::get_uuid
+const PROJECT_COLUMNS: &str = "project.name, project.asset_price, project.token_name, project.share_count, project.investors_share, project.creator, project.shares_asset_id, project.central_app_id, project.invest_program, project.staking_program, project.central_program, project.customer_program, project.uuid::TEXT, project.slug";
+const PROJECT_COLUMN_COUNT: usize = 14;
+
 #[async_trait]
 impl ProjectDao for ProjectDaoImpl {
-    async fn init(&self) -> Result<()> {
-        let _ = self
-            .client
+    async fn save_project(&self, project: &Project, slug: &str) -> Result<(), ServiceError> {
+        let mut client = get_client(&self.pool).await?;
+        let tx = client.transaction().await?;
+        tx.execute(
+                "INSERT INTO project (name, creator, asset_price, token_name, share_count, investors_share, shares_asset_id, central_app_id, invest_program, staking_program, central_program, customer_program, uuid, slug) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13::TEXT::UUID, $14);",
+                &[
+                    &project.specs.name,
+                    &project.creator.to_string(),
+                    &to_numeric(project.specs.asset_price.0),
+                    &project.specs.shares.token_name.to_string(),
+                    &to_numeric(project.specs.shares.count),
+                    &to_smallint(project.specs.investors_share)?,
+                    &to_bigint(project.shares_asset_id)?,
+                    &to_bigint(project.central_app_id)?,
+                    &project.invest_escrow.program.0,
+                    &project.staking_escrow.program.0,
+                    &project.central_escrow.program.0,
+                    &project.customer_escrow.program.0,
+                    &project.uuid.to_string(),
+                    &slug,
+                ],
+            )
+            .await?;
+        tx.execute(
+            "INSERT INTO project_slug (slug, project_id) SELECT slug, id FROM project WHERE uuid=$1::TEXT::UUID;",
+            &[&project.uuid.to_string()],
+        )
+        .await?;
+        tx.commit().await?;
+
+        log::debug!("Saved project: {}, slug: {}", project.uuid, slug);
+
+        Ok(())
+    }
+
+    async fn load_project(&self, uuid: &Uuid) -> Result<StoredProject, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let project_rows = client
+            .query(
+                format!(
+                    "SELECT {} FROM project WHERE uuid=$1::TEXT::UUID AND archived_at IS NULL;",
+                    PROJECT_COLUMNS
+                )
+                .as_str(),
+                &[&uuid.to_string()],
+            )
+            .await?;
+
+        match project_rows.as_slice() {
+            [row] => Ok(project_from_row(row)?),
+            _ => Err(ServiceError::NotFound(format!(
+                "Project not found for uuid: {}",
+                uuid
+            ))),
+        }
+    }
+
+    async fn load_project_with_slug(&self, slug: &str) -> Result<StoredProject, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let project_rows = client
+            .query(
+                format!(
+                    "SELECT {} FROM project_slug JOIN project ON project.id = project_slug.project_id
+                    WHERE project_slug.slug=$1 AND project.archived_at IS NULL;",
+                    PROJECT_COLUMNS
+                )
+                .as_str(),
+                &[&slug],
+            )
+            .await?;
+
+        match project_rows.as_slice() {
+            [row] => Ok(project_from_row(row)?),
+            _ => Err(ServiceError::NotFound(format!(
+                "Project not found for slug: {}",
+                slug
+            ))),
+        }
+    }
+
+    async fn load_legacy_project_uuid(&self, id: i32) -> Result<Uuid, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT uuid::TEXT FROM project WHERE id=$1 AND archived_at IS NULL;",
+                &[&id],
+            )
+            .await?;
+
+        match rows.as_slice() {
+            [row] => Ok(get_uuid(row, 0)?),
+            _ => Err(ServiceError::NotFound(format!("Project not found: {}", id))),
+        }
+    }
+
+    async fn update_project_name(
+        &self,
+        uuid: &Uuid,
+        name: &str,
+        slug: &str,
+    ) -> Result<(), ServiceError> {
+        let mut client = get_client(&self.pool).await?;
+        let tx = client.transaction().await?;
+        let updated = tx
             .execute(
-                "CREATE TABLE IF NOT EXISTS project(
-            id SERIAL PRIMARY KEY,
-            name TEXT NOT NULL,
-            creator TEXT NOT NULL,
-            asset_price TEXT NOT NULL,
-            token_name TEXT NOT NULL,
-            share_count TEXT NOT NULL,
-            investors_share TEXT NOT NULL,
-            share_id TEXT NOT NULL,
-            app_id TEXT NOT NULL,
-            invest_b TEXT NOT NULL,
-            staking_b TEXT NOT NULL,
-            central_b TEXT NOT NULL,
-            customer_b TEXT NOT NULL,
-            uuid TEXT NOT NULL
-        );",
-                &[],
+                "UPDATE project SET name=$2, slug=$3, updated_at=now() WHERE uuid=$1::TEXT::UUID AND archived_at IS NULL;",
+                &[&uuid.to_string(), &name, &slug],
             )
             .await?;
-        // note: execute returns "rows modified", for create table it's always 0
+        expect_updated(updated, uuid)?;
+        // the slug may be a previous one of this project (no-op update), but not of another project
+        let inserted = tx
+            .execute(
+                "INSERT INTO project_slug (slug, project_id) SELECT slug, id FROM project WHERE uuid=$1::TEXT::UUID
+                ON CONFLICT (slug) DO UPDATE SET slug=excluded.slug WHERE project_slug.project_id=excluded.project_id;",
+                &[&uuid.to_string()],
+            )
+            .await?;
+        if inserted == 0 {
+            return Err(ServiceError::Conflict(format!(
+                "Slug taken by another project: {}",
+                slug
+            )));
+        }
+        tx.commit().await?;
         Ok(())
     }
 
-    async fn save_project(&self, project: &Project) -> Result<String> {
-        let id_rows = self.client
+    async fn load_taken_slugs(
+        &self,
+        base: &str,
+        except: Option<&Uuid>,
+    ) -> Result<HashSet<String>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
             .query(
-                "INSERT INTO project (name, creator, asset_price, token_name, share_count, investors_share, share_id, app_id, invest_b, staking_b, central_b, customer_b, uuid) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13) RETURNING id;",
+                "SELECT project_slug.slug FROM project_slug JOIN project ON project.id = project_slug.project_id
+                WHERE (project_slug.slug=$1 OR project_slug.slug LIKE $2)
+                AND ($3::TEXT IS NULL OR project.uuid <> $3::TEXT::UUID);",
                 &[
-                    &project.specs.name,
-                    &project.creator.to_string(),
-                    &project.specs.asset_price.0.to_string(),
-                    &project.specs.shares.token_name.to_string(),
-                    &project.specs.shares.count.to_string(),
-                    &project.specs.investors_share.to_string(),
-                    &project.shares_asset_id.to_string(),
-                    &project.central_app_id.to_string(),
-                    &BASE64.encode(&project.invest_escrow.program.0),
-                    &BASE64.encode(&project.staking_escrow.program.0),
-                    &BASE64.encode(&project.central_escrow.program.0),
-                    &BASE64.encode(&project.customer_escrow.program.0),
-                    &project.uuid.to_string(),
+                    &base,
+                    // slugs don't contain LIKE wildcards
+                    &format!("{}-%", base),
+                    &except.map(|uuid| uuid.to_string()),
                 ],
             )
             .await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn archive_project(&self, uuid: &Uuid) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let updated = client
+            .execute(
+                "UPDATE project SET archived_at=now(), updated_at=now() WHERE uuid=$1::TEXT::UUID AND archived_at IS NULL;",
+                &[&uuid.to_string()],
+            )
+            .await?;
+        expect_updated(updated, uuid)
+    }
 
-        let id_row = match id_rows.as_slice() {
-            [row] => row,
-            _ => return Err(anyhow!("Unexpected row count: {}", id_rows.len())),
+    async fn list_projects(&self, query: &ProjectQuery) -> Result<ProjectPage, ServiceError> {
+        let (column, column_type) = query.sort.column();
+        let (direction, comparison) = match query.order {
+            SortOrder::Asc => ("ASC", ">"),
+            SortOrder::Desc => ("DESC", "<"),
         };
-        let id: i32 = id_row.get(0);
-        let id_str = id.to_string();
 
-        log::debug!("Saved project, row id: {}", id_str);
+        let creator = query.creator.map(|a| a.to_string());
+        // one more, to know whether there's a next page
+        let limit = i64::try_from(query.limit).map_err(anyhow::Error::from)? + 1;
 
-        Ok(id_str)
-    }
+        let mut conditions = vec!["archived_at IS NULL".to_owned()];
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![];
+        if let Some(creator) = &creator {
+            params.push(creator);
+            conditions.push(format!("creator=${}", params.len()));
+        }
+        if let Some(token_name) = &query.token_name {
+            params.push(token_name);
+            conditions.push(format!("token_name=${}", params.len()));
+        }
+        if let Some(after) = &query.after {
+            params.push(&after.value);
+            params.push(&after.id);
+            conditions.push(format!(
+                "({}, id) {} (${}::TEXT::{}, ${})",
+                column,
+                comparison,
+                params.len() - 1,
+                column_type,
+                params.len()
+            ));
+        }
+        params.push(&limit);
 
-    async fn load_project(&self, id: i32) -> Result<Project> {
-        let project_rows = self.client.query(
-            "SELECT name, asset_price, token_name, share_count, investors_share, creator, share_id, app_id, invest_b, staking_b, central_b, customer_b, uuid FROM project WHERE id=$1;", 
-            &[&id]).await?;
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                format!(
+                    "SELECT {}, id, {}::TEXT FROM project WHERE {} ORDER BY {} {}, id {} LIMIT ${};",
+                    PROJECT_COLUMNS,
+                    column,
+                    conditions.join(" AND "),
+                    column,
+                    direction,
+                    direction,
+                    params.len()
+                )
+                .as_str(),
+                &params,
+            )
+            .await?;
 
-        let project_row = match project_rows.as_slice() {
-            [row] => row,
-            _ => return Err(anyhow!("Project not found: {}", id)),
-        };
+        let mut projects = vec![];
+        let mut next_cursor = None;
+        for row in rows.iter().take(query.limit) {
+            let id: i32 = row.get(PROJECT_COLUMN_COUNT);
+            next_cursor = Some(ProjectCursor {
+                value: row.get(PROJECT_COLUMN_COUNT + 1),
+                id,
+            });
+            projects.push(project_from_row(row)?);
+        }
+        if rows.len() <= query.limit {
+            next_cursor = None;
+        }
 
-        Ok(Project {
-            specs: CreateProjectSpecs {
-                name: project_row.get(0),
-                asset_price: get_microalgos(project_row, 1)?,
-                shares: CreateSharesSpecs {
-                    token_name: project_row.get(2),
-                    count: get_u64(project_row, 3)?,
-                },
-                investors_share: get_u64(project_row, 4)?,
-            },
-            creator: get_address(project_row, 5)?,
-            shares_asset_id: get_u64(project_row, 6)?,
-            central_app_id: get_u64(project_row, 7)?,
-            invest_escrow: ContractAccount::new(get_bytes(project_row, 8)?),
-            staking_escrow: ContractAccount::new(get_bytes(project_row, 9)?),
-            central_escrow: ContractAccount::new(get_bytes(project_row, 10)?),
-            customer_escrow: ContractAccount::new(get_bytes(project_row, 11)?),
-            uuid: project_row.get::<_, String>(12).parse()?,
+        Ok(ProjectPage {
+            projects,
+            next_cursor,
         })
     }
 
-    // copy of load_project that queries with uuid - not refactoring yet as we'll remove load_project soon likely
-    async fn load_project_with_uuid(&self, uuid: &Uuid) -> Result<Project> {
-        let project_rows = self.client.query(
-            "SELECT name, asset_price, token_name, share_count, investors_share, creator, share_id, app_id, invest_b, staking_b, central_b, customer_b, uuid FROM project WHERE uuid=$1;", 
-            &[&uuid.to_string()]).await?;
+    async fn load_all_projects(&self) -> Result<Vec<StoredProject>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                format!(
+                    "SELECT {} FROM project WHERE archived_at IS NULL ORDER BY id;",
+                    PROJECT_COLUMNS
+                )
+                .as_str(),
+                &[],
+            )
+            .await?;
 
-        let project_row = match project_rows.as_slice() {
-            [row] => row,
-            _ => return Err(anyhow!("Project not found for uuid: {}", uuid)),
-        };
+        Ok(rows
+            .iter()
+            .map(project_from_row)
+            .collect::<anyhow::Result<Vec<_>>>()?)
+    }
 
-        Ok(Project {
-            specs: CreateProjectSpecs {
-                name: project_row.get(0),
-                asset_price: get_microalgos(project_row, 1)?,
-                shares: CreateSharesSpecs {
-                    token_name: project_row.get(2),
-                    count: get_u64(project_row, 3)?,
-                },
-                investors_share: get_u64(project_row, 4)?,
-            },
-            creator: get_address(project_row, 5)?,
-            shares_asset_id: get_u64(project_row, 6)?,
-            central_app_id: get_u64(project_row, 7)?,
-            invest_escrow: ContractAccount::new(get_bytes(project_row, 8)?),
-            staking_escrow: ContractAccount::new(get_bytes(project_row, 9)?),
-            central_escrow: ContractAccount::new(get_bytes(project_row, 10)?),
-            customer_escrow: ContractAccount::new(get_bytes(project_row, 11)?),
-            uuid: project_row.get::<_, String>(12).parse()?,
-        })
+    async fn search_projects(
+        &self,
+        tsquery: &str,
+        limit: usize,
+    ) -> Result<Vec<ProjectSearchHit>, ServiceError> {
+        let highlight_options = format!(
+            "StartSel={}, StopSel={}, HighlightAll=true",
+            HIGHLIGHT_START, HIGHLIGHT_STOP
+        );
+        let fragment_options = format!(
+            "StartSel={}, StopSel={}, MaxFragments=2",
+            HIGHLIGHT_START, HIGHLIGHT_STOP
+        );
+        let limit = i64::try_from(limit).map_err(anyhow::Error::from)?;
+
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                format!(
+                    "SELECT {}, ts_headline('simple', project.name, query, $2),
+                        ts_rank(project.full_search_vector, query) AS rank,
+                        ts_headline('simple', nullif(project_metadata.description, ''), query, $4)
+                    FROM project LEFT JOIN project_metadata ON project_metadata.project_id = project.id,
+                        to_tsquery('simple', $1) query
+                    WHERE project.archived_at IS NULL AND project.full_search_vector @@ query
+                    ORDER BY rank DESC, project.id DESC LIMIT $3;",
+                    PROJECT_COLUMNS
+                )
+                .as_str(),
+                &[&tsquery, &highlight_options, &limit, &fragment_options],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ProjectSearchHit {
+                    project: project_from_row(row)?,
+                    name_highlight: row.get(PROJECT_COLUMN_COUNT),
+                    rank: row.get(PROJECT_COLUMN_COUNT + 1),
+                    description_highlight: row.get(PROJECT_COLUMN_COUNT + 2),
+                })
+            })
+            .collect()
     }
 }
 
+fn expect_updated(row_count: u64, uuid: &Uuid) -> Result<(), ServiceError> {
+    match row_count {
+        1 => Ok(()),
+        0 => Err(ServiceError::NotFound(format!(
+            "Project not found for uuid: {}",
+            uuid
+        ))),
+        _ => Err(anyhow!("Unexpected row count: {}", row_count).into()),
+    }
+}
+
+/// Maps a row selected with PROJECT_COLUMNS
+fn project_from_row(row: &Row) -> anyhow::Result<StoredProject> {
+    let project = Project {
+        specs: CreateProjectSpecs {
+            name: row.get(0),
+            asset_price: get_microalgos(row, 1)?,
+            shares: CreateSharesSpecs {
+                token_name: row.get(2),
+                count: get_u64(row, 3)?,
+            },
+            investors_share: get_small_u64(row, 4)?,
+        },
+        creator: get_address(row, 5)?,
+        shares_asset_id: get_id(row, 6)?,
+        central_app_id: get_id(row, 7)?,
+        invest_escrow: ContractAccount::new(get_bytes(row, 8)?),
+        staking_escrow: ContractAccount::new(get_bytes(row, 9)?),
+        central_escrow: ContractAccount::new(get_bytes(row, 10)?),
+        customer_escrow: ContractAccount::new(get_bytes(row, 11)?),
+        uuid: get_uuid(row, 12)?,
+    };
+    Ok(StoredProject {
+        project,
+        slug: row.get(13),
+    })
+}
+
 #[cfg(test)]
 mod test {
-    use std::{convert::TryInto, sync::Arc};
+    use std::convert::TryInto;
 
     use super::{ProjectDao, ProjectDaoImpl};
-    use crate::{dao::db::create_db_client, logger::init_logger};
+    use crate::{
+        config::test_config, dao::db::create_db_pool, error::ServiceError, logger::init_logger,
+        test_data::project_json,
+    };
     use anyhow::{Error, Result};
-    use core_::api::json_workaround::ProjectJson;
+    use core_::flows::create_project::model::Project;
     use tokio::test;
+    use uuid::Uuid;
 
+    // to be executed after migrations::test::test_migrate
     #[test]
     #[ignore]
-    async fn test_create_table() -> Result<()> {
-        init_logger();
+    async fn test_insert_and_load_a_project() -> Result<()> {
+        init_logger(&test_config()?.log_config_path);
         let project_dao = create_test_project_dao().await?;
 
-        project_dao.init().await?;
+        // insert
+        let project_json = project_json()?;
+
+        let project = project_json.try_into().map_err(Error::msg)?;
+
+        project_dao
+            .save_project(&project, &format!("test-{}", project.uuid))
+            .await?;
+
+        let loaded_project = project_dao.load_project(&project.uuid).await?;
+        // println!("project: {:?}", loaded_project);
+
+        assert_eq!(project, loaded_project.project);
+
         Ok(())
     }
 
-    // to be executed after test_create_table
     #[test]
     #[ignore]
-    async fn test_insert_and_load_a_project() -> Result<()> {
-        init_logger();
+    async fn test_update_and_archive_a_project() -> Result<()> {
         let project_dao = create_test_project_dao().await?;
 
-        // insert
-        // generated with client app - convenience to test quickly, should be replaced with regular mock data
-        let json = r#"{"specs":{"name":"my1project","shares":{"token_name":"foo","count":100},"investors_share":40,"asset_price":1000000},"creator_address":"MKRBTLNZRS3UZZDS5OWPLP7YPHUDNKXFUFN5PNCJ3P2XRG74HNOGY6XOYQ","shares_asset_id":42,"central_app_id":50,"invest_escrow":{"address":"SV2LIUFR5AL2BZOMGW3SAYU5FT2T662NOXPVKXF3GKGTDYRZJMHENNZS2Y","program":[4,32,6,6,42,0,232,7,43,4,50,4,34,18,51,2,17,35,18,16,51,3,17,33,4,18,16,64,0,9,50,4,34,18,64,0,83,36,67,51,2,17,35,18,51,2,16,33,5,18,16,51,2,18,36,18,16,51,2,1,37,14,16,51,2,32,50,3,18,16,51,2,21,50,3,18,16,51,3,17,33,4,18,16,51,3,16,33,5,18,16,51,3,18,36,18,16,51,3,1,37,14,16,51,3,32,50,3,18,16,51,3,21,50,3,18,16,66,0,91,51,0,16,34,18,51,3,17,35,18,16,51,3,20,128,32,247,10,15,104,164,223,249,27,116,139,66,224,167,91,33,215,215,35,34,187,44,221,159,36,227,39,167,77,162,152,169,0,18,16,51,3,1,37,14,16,51,3,21,50,3,18,16,51,3,32,50,3,18,16,51,1,8,51,3,18,129,192,132,61,11,18,16,51,3,18,51,4,18,18,16]},"staking_escrow":{"address":"64FA62FE374RW5ELILQKOWZB27LSGIV3FTOZ6JHDE6TU3IUYVEAKZXC3DQ","program":[4,32,6,4,6,0,42,43,232,7,50,4,35,18,51,0,17,37,18,16,51,1,17,33,4,18,16,64,0,18,50,4,129,2,18,64,0,89,50,4,129,3,18,64,0,93,36,67,51,0,17,37,18,51,0,16,34,18,16,51,0,18,36,18,16,51,0,1,33,5,14,16,51,0,32,50,3,18,16,51,0,21,50,3,18,16,51,1,17,33,4,18,16,51,1,16,34,18,16,51,1,18,36,18,16,51,1,1,33,5,14,16,51,1,32,50,3,18,16,51,1,21,50,3,18,16,67,51,0,16,35,18,51,1,16,34,18,16,67,51,0,16,35,18,51,1,16,34,18,16,51,2,16,129,1,18,16]},"central_escrow":{"address":"P7GEWDXXW5IONRW6XRIRVPJCT2XXEQGOBGG65VJPBUOYZEJCBZWTPHS3VQ","program":[4,129,1]},"customer_escrow":{"address":"3BW2V2NE7AIFGSARHF7ULZFWJPCOYOJTP3NL6ZQ3TWMSK673HTWTPPKEBA","program":[4,32,1,1,50,4,129,3,18,64,0,3,129,0,67,51,0,16,129,6,18,51,1,16,34,18,16,51,1,1,129,232,7,14,16,51,1,32,50,3,18,16,51,1,21,50,3,18,16,51,1,7,128,32,127,204,75,14,247,183,80,230,198,222,188,81,26,189,34,158,175,114,64,206,9,141,238,213,47,13,29,140,145,34,14,109,18,16,51,2,16,34,18,16]},"uuid":"f5c8614f-f969-4e65-8039-15048a5055dd"}"#;
-        let project_json = serde_json::from_str::<ProjectJson>(json)?;
-
-        let project = project_json.try_into().map_err(Error::msg)?;
+        let mut project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        project.uuid = Uuid::new_v4();
+        let slug = format!("test-{}", project.uuid);
+        project_dao.save_project(&project, &slug).await?;
 
-        let id = project_dao.save_project(&project).await?;
-        println!("id: {:?}", id);
-
-        let loaded_project = project_dao.load_project(id.parse()?).await?;
-        // println!("project: {:?}", loaded_project);
+        let new_slug = format!("renamed-{}", project.uuid);
+        project_dao
+            .update_project_name(&project.uuid, "renamed", &new_slug)
+            .await?;
+        let loaded_project = project_dao.load_project(&project.uuid).await?;
+        assert_eq!("renamed", loaded_project.project.specs.name);
+        // the old slug still resolves
+        let loaded_project = project_dao.load_project_with_slug(&slug).await?;
+        assert_eq!(new_slug, loaded_project.slug);
 
-        assert_eq!(project, loaded_project);
+        project_dao.archive_project(&project.uuid).await?;
+        let res = project_dao.load_project(&project.uuid).await;
+        assert!(matches!(res, Err(ServiceError::NotFound(_))));
 
         Ok(())
     }
 
     async fn create_test_project_dao() -> Result<Box<dyn ProjectDao>> {
-        let client = create_db_client().await?;
         Ok(Box::new(ProjectDaoImpl {
-            client: Arc::new(client),
+            pool: create_db_pool(&test_config()?.db)?,
         }))
     }
 }
@@ -0,0 +1,210 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::db::{get_client, get_optional_uuid};
+
+#[async_trait]
+pub trait ProjectMetadataDao: Sync + Send {
+    /// Projects without saved metadata get the default (empty) metadata
+    async fn load_metadata(&self, uuid: &Uuid) -> Result<ProjectMetadata, ServiceError>;
+    /// Replaces the metadata of the project, except the images
+    async fn save_metadata(
+        &self,
+        uuid: &Uuid,
+        metadata: &ProjectMetadata,
+    ) -> Result<(), ServiceError>;
+    /// Sets the uploaded images (see image_dao) of the project
+    async fn save_images(
+        &self,
+        uuid: &Uuid,
+        logo_image_id: Option<&Uuid>,
+        cover_image_id: Option<&Uuid>,
+    ) -> Result<(), ServiceError>;
+}
+
+pub struct ProjectMetadataDaoImpl {
+    pub pool: Pool,
+}
+
+/// Off-chain project information, for the frontend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectMetadata {
+    /// Markdown
+    #[serde(default)]
+    pub description: String,
+    pub logo_url: Option<String>,
+    pub cover_url: Option<String>,
+    pub website_url: Option<String>,
+    #[serde(default)]
+    pub social_links: Vec<String>,
+    pub category: Option<ProjectCategory>,
+    /// Uploaded images, served at /images/<id>. Set with their own endpoint.
+    #[serde(skip_deserializing)]
+    pub logo_image_id: Option<Uuid>,
+    #[serde(skip_deserializing)]
+    pub cover_image_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectCategory {
+    Art,
+    Education,
+    Entertainment,
+    Finance,
+    Food,
+    Gaming,
+    Health,
+    Retail,
+    Services,
+    Software,
+    Other,
+}
+
+impl ProjectCategory {
+    const ALL: [ProjectCategory; 11] = [
+        ProjectCategory::Art,
+        ProjectCategory::Education,
+        ProjectCategory::Entertainment,
+        ProjectCategory::Finance,
+        ProjectCategory::Food,
+        ProjectCategory::Gaming,
+        ProjectCategory::Health,
+        ProjectCategory::Retail,
+        ProjectCategory::Services,
+        ProjectCategory::Software,
+        ProjectCategory::Other,
+    ];
+
+    /// Stored value, same as the json one
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProjectCategory::Art => "art",
+            ProjectCategory::Education => "education",
+            ProjectCategory::Entertainment => "entertainment",
+            ProjectCategory::Finance => "finance",
+            ProjectCategory::Food => "food",
+            ProjectCategory::Gaming => "gaming",
+            ProjectCategory::Health => "health",
+            ProjectCategory::Retail => "retail",
+            ProjectCategory::Services => "services",
+            ProjectCategory::Software => "software",
+            ProjectCategory::Other => "other",
+        }
+    }
+
+    fn parse(str: &str) -> anyhow::Result<ProjectCategory> {
+        ProjectCategory::ALL
+            .iter()
+            .find(|c| c.as_str() == str)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown project category: {}", str))
+    }
+}
+
+#[async_trait]
+impl ProjectMetadataDao for ProjectMetadataDaoImpl {
+    async fn load_metadata(&self, uuid: &Uuid) -> Result<ProjectMetadata, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT m.description, m.logo_url, m.cover_url, m.website_url, m.social_links, m.category,
+                    m.logo_image_id::TEXT, m.cover_image_id::TEXT
+                FROM project_metadata m JOIN project p ON p.id = m.project_id
+                WHERE p.uuid=$1::TEXT::UUID;",
+                &[&uuid.to_string()],
+            )
+            .await?;
+
+        match rows.as_slice() {
+            [row] => Ok(metadata_from_row(row)?),
+            _ => Ok(ProjectMetadata::default()),
+        }
+    }
+
+    async fn save_metadata(
+        &self,
+        uuid: &Uuid,
+        metadata: &ProjectMetadata,
+    ) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let updated = client
+            .execute(
+                "INSERT INTO project_metadata (project_id, description, logo_url, cover_url, website_url, social_links, category)
+                SELECT id, $2, $3, $4, $5, $6, $7 FROM project WHERE uuid=$1::TEXT::UUID AND archived_at IS NULL
+                ON CONFLICT (project_id) DO UPDATE SET
+                    description=excluded.description, logo_url=excluded.logo_url, cover_url=excluded.cover_url,
+                    website_url=excluded.website_url, social_links=excluded.social_links, category=excluded.category,
+                    updated_at=now();",
+                &[
+                    &uuid.to_string(),
+                    &metadata.description,
+                    &metadata.logo_url,
+                    &metadata.cover_url,
+                    &metadata.website_url,
+                    &metadata.social_links,
+                    &metadata.category.map(|c| c.as_str()),
+                ],
+            )
+            .await?;
+
+        expect_project_found(updated, uuid)
+    }
+
+    async fn save_images(
+        &self,
+        uuid: &Uuid,
+        logo_image_id: Option<&Uuid>,
+        cover_image_id: Option<&Uuid>,
+    ) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let updated = client
+            .execute(
+                "INSERT INTO project_metadata (project_id, logo_image_id, cover_image_id)
+                SELECT id, $2::TEXT::UUID, $3::TEXT::UUID FROM project WHERE uuid=$1::TEXT::UUID AND archived_at IS NULL
+                ON CONFLICT (project_id) DO UPDATE SET
+                    logo_image_id=excluded.logo_image_id, cover_image_id=excluded.cover_image_id, updated_at=now();",
+                &[
+                    &uuid.to_string(),
+                    &logo_image_id.map(|id| id.to_string()),
+                    &cover_image_id.map(|id| id.to_string()),
+                ],
+            )
+            .await?;
+        expect_project_found(updated, uuid)
+    }
+}
+
+/// The upserts insert nothing if the project doesn't exist
+fn expect_project_found(row_count: u64, uuid: &Uuid) -> Result<(), ServiceError> {
+    if row_count == 0 {
+        return Err(ServiceError::NotFound(format!(
+            "Project not found for uuid: {}",
+            uuid
+        )));
+    }
+    Ok(())
+}
+
+fn metadata_from_row(row: &Row) -> anyhow::Result<ProjectMetadata> {
+    Ok(ProjectMetadata {
+        description: row.get(0),
+        logo_url: row.get(1),
+        cover_url: row.get(2),
+        website_url: row.get(3),
+        social_links: row.get(4),
+        category: row
+            .get::<_, Option<String>>(5)
+            .map(|c| ProjectCategory::parse(&c))
+            .transpose()?,
+        logo_image_id: get_optional_uuid(row, 6)?,
+        cover_image_id: get_optional_uuid(row, 7)?,
+    })
+}
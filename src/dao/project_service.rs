@@ -1,51 +1,533 @@
-use anyhow::Result;
-use core_::{api::model::ProjectForUsers, flows::create_project::model::Project};
+use std::future::Future;
 
-use crate::{frontend_host, Env};
+use algonaut::core::Address;
+use chrono::DateTime;
+use core_::{
+    api::{json_workaround::ProjectForUsersJson, model::ProjectForUsers},
+    flows::create_project::model::Project,
+};
+use data_encoding::BASE64URL_NOPAD;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use super::project_dao::ProjectDao;
+use crate::{
+    auth::signature::verify_project_signature,
+    chain::project_verifier::ProjectVerifier,
+    config::Config,
+    error::ServiceError,
+    images::parse_image_id,
+    markdown::render_markdown,
+    search::{highlight_to_html, to_prefix_tsquery},
+    slug::{slugify, unique_slug},
+    validation::{validate_metadata, validate_project_name, FieldError},
+};
+
+use super::{
+    image_dao::ImageDao,
+    project_dao::{ProjectCursor, ProjectDao, ProjectQuery, ProjectSort, SortOrder, StoredProject},
+    project_metadata_dao::{ProjectMetadata, ProjectMetadataDao},
+    project_stats_dao::ProjectStatsDao,
+    project_stats_service::{to_project_stats_json, ProjectStatsJson},
+};
+
+pub const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 100;
+/// Saving with a generated slug is retried when another project took it in the meantime
+const SLUG_ATTEMPTS: usize = 3;
 
 pub async fn save_project(
     dao: &dyn ProjectDao,
-    env: &Env,
+    verifier: &dyn ProjectVerifier,
+    config: &Config,
     project: &Project,
-) -> Result<ProjectForUsers> {
-    let project_id = dao.save_project(project).await?;
-    Ok(to_project_for_users(env, &project_id, project))
+    creator_signature: &str,
+) -> Result<ProjectForUsers, ServiceError> {
+    verify_project_signature(project, creator_signature)?;
+    verifier.verify(project).await?;
+    let slug = with_free_slug(dao, &project.specs.name, None, |slug| async move {
+        dao.save_project(project, &slug).await
+    })
+    .await?;
+    Ok(to_project_for_users(config, project, &slug))
+}
+
+/// The project for users, with its metadata and funding statistics
+#[derive(Debug)]
+pub struct ProjectView {
+    pub project: ProjectForUsers,
+    pub metadata: ProjectMetadata,
+    pub stats: ProjectStatsJson,
+}
+
+#[derive(Serialize)]
+pub struct ProjectViewJson {
+    #[serde(flatten)]
+    pub project: ProjectForUsersJson,
+    #[serde(flatten)]
+    pub metadata: ProjectMetadata,
+    /// The description rendered and sanitized
+    pub description_html: String,
+    pub stats: ProjectStatsJson,
+}
+
+impl From<ProjectView> for ProjectViewJson {
+    fn from(view: ProjectView) -> Self {
+        ProjectViewJson {
+            project: view.project.into(),
+            description_html: render_markdown(&view.metadata.description),
+            metadata: view.metadata,
+            stats: view.stats,
+        }
+    }
+}
+
+pub async fn load_project_view(
+    dao: &dyn ProjectDao,
+    metadata_dao: &dyn ProjectMetadataDao,
+    stats_dao: &dyn ProjectStatsDao,
+    config: &Config,
+    uuid: &str,
+) -> Result<ProjectView, ServiceError> {
+    let stored = dao.load_project(&parse_uuid(uuid)?).await?;
+    to_project_view(metadata_dao, stats_dao, config, &stored).await
+}
+
+#[derive(Debug)]
+pub enum ProjectViewWithSlug {
+    Found(ProjectView),
+    /// The slug is a previous one: the project's current slug
+    Moved(String),
+}
+
+pub async fn load_project_view_with_slug(
+    dao: &dyn ProjectDao,
+    metadata_dao: &dyn ProjectMetadataDao,
+    stats_dao: &dyn ProjectStatsDao,
+    config: &Config,
+    slug: &str,
+) -> Result<ProjectViewWithSlug, ServiceError> {
+    let stored = dao.load_project_with_slug(slug).await?;
+    Ok(if stored.slug == slug {
+        ProjectViewWithSlug::Found(to_project_view(metadata_dao, stats_dao, config, &stored).await?)
+    } else {
+        ProjectViewWithSlug::Moved(stored.slug)
+    })
+}
+
+/// Replaces the metadata. Only the creator can do this.
+pub async fn update_project_metadata(
+    dao: &dyn ProjectDao,
+    metadata_dao: &dyn ProjectMetadataDao,
+    stats_dao: &dyn ProjectStatsDao,
+    config: &Config,
+    caller: &Address,
+    uuid: &str,
+    metadata: ProjectMetadata,
+) -> Result<ProjectView, ServiceError> {
+    let metadata = validate_metadata(metadata)?;
+    let uuid = parse_uuid(uuid)?;
+    let stored = load_own_project(dao, caller, &uuid).await?;
+    metadata_dao.save_metadata(&uuid, &metadata).await?;
+    // reloaded for the images, which aren't part of the update
+    to_project_view(metadata_dao, stats_dao, config, &stored).await
+}
+
+/// Images uploaded by the creator. None removes the image.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectImagesJson {
+    pub logo_image_id: Option<String>,
+    pub cover_image_id: Option<String>,
 }
 
-pub async fn load_project_for_users(
+pub async fn update_project_images(
+    dao: &dyn ProjectDao,
+    metadata_dao: &dyn ProjectMetadataDao,
+    image_dao: &dyn ImageDao,
+    stats_dao: &dyn ProjectStatsDao,
+    config: &Config,
+    caller: &Address,
+    uuid: &str,
+    images: &ProjectImagesJson,
+) -> Result<ProjectView, ServiceError> {
+    let uuid = parse_uuid(uuid)?;
+    let stored = load_own_project(dao, caller, &uuid).await?;
+
+    let logo_image_id = own_image_id(image_dao, caller, images.logo_image_id.as_deref()).await?;
+    let cover_image_id = own_image_id(image_dao, caller, images.cover_image_id.as_deref()).await?;
+    metadata_dao
+        .save_images(&uuid, logo_image_id.as_ref(), cover_image_id.as_ref())
+        .await?;
+
+    to_project_view(metadata_dao, stats_dao, config, &stored).await
+}
+
+/// Parses the id, checking that the image exists and was uploaded by the caller
+async fn own_image_id(
+    image_dao: &dyn ImageDao,
+    caller: &Address,
+    id: Option<&str>,
+) -> Result<Option<Uuid>, ServiceError> {
+    let id = match id {
+        Some(id) => parse_image_id(id)?,
+        None => return Ok(None),
+    };
+    let image = image_dao.load_image(&id).await?;
+    if image.uploader != *caller {
+        return Err(ServiceError::Forbidden(format!(
+            "Image wasn't uploaded by the caller: {}",
+            id
+        )));
+    }
+    Ok(Some(id))
+}
+
+async fn to_project_view(
+    metadata_dao: &dyn ProjectMetadataDao,
+    stats_dao: &dyn ProjectStatsDao,
+    config: &Config,
+    stored: &StoredProject,
+) -> Result<ProjectView, ServiceError> {
+    let stats = stats_dao.load_stats(&stored.project.uuid).await?;
+    Ok(ProjectView {
+        project: to_project_for_users(config, &stored.project, &stored.slug),
+        metadata: metadata_dao.load_metadata(&stored.project.uuid).await?,
+        stats: to_project_stats_json(&stored.project, stats),
+    })
+}
+
+pub async fn load_project(dao: &dyn ProjectDao, uuid: &str) -> Result<Project, ServiceError> {
+    Ok(dao.load_project(&parse_uuid(uuid)?).await?.project)
+}
+
+/// A free slug for the name. `uuid`: of the project being renamed, which can take back its previous slugs.
+async fn generate_slug(
+    dao: &dyn ProjectDao,
+    name: &str,
+    uuid: Option<&Uuid>,
+) -> Result<String, ServiceError> {
+    let base = slugify(name);
+    let taken = dao.load_taken_slugs(&base, uuid).await?;
+    Ok(unique_slug(&base, &taken))
+}
+
+/// Generates a free slug and saves it with `save`. The slug can be taken by another project between both:
+/// the unique constraints reject it (Conflict) and it's generated again. Returns the saved slug.
+async fn with_free_slug<F, Fut>(
+    dao: &dyn ProjectDao,
+    name: &str,
+    uuid: Option<&Uuid>,
+    save: F,
+) -> Result<String, ServiceError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), ServiceError>>,
+{
+    let mut attempt = 1;
+    loop {
+        let slug = generate_slug(dao, name, uuid).await?;
+        match save(slug.clone()).await {
+            Err(ServiceError::Conflict(e)) if attempt < SLUG_ATTEMPTS => {
+                log::debug!("Saving with slug: {} failed, retrying: {}", slug, e);
+                attempt += 1;
+            }
+            res => return res.map(|_| slug),
+        }
+    }
+}
+
+/// Deprecated: only to redirect the old id based routes.
+pub async fn load_legacy_project_uuid(
     dao: &dyn ProjectDao,
-    env: &Env,
     id: &str,
-) -> Result<ProjectForUsers> {
-    let project = dao.load_project(id.parse()?).await?;
-    Ok(to_project_for_users(env, id, &project))
+) -> Result<Uuid, ServiceError> {
+    dao.load_legacy_project_uuid(parse_id(id)?).await
+}
+
+/// The fields of a project that can be changed after it was saved.
+/// The on-chain ones (app, asset, escrows) are immutable: unknown fields are rejected.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateProjectJson {
+    pub name: String,
 }
 
-pub async fn load_project_for_users_with_uuid(
+pub async fn update_project(
     dao: &dyn ProjectDao,
-    env: &Env,
+    config: &Config,
+    caller: &Address,
     uuid: &str,
-) -> Result<ProjectForUsers> {
-    let project = dao.load_project_with_uuid(&uuid.parse()?).await?;
-    // TODO temporary hack: passing 0 as project id. For some reason the current implementation doesn't load the id from the db,
-    // not doing major changes yet as we plan to remove the db id entirely (use only uuid, at least for external queries).
-    Ok(to_project_for_users(env, "0", &project))
+    update: &UpdateProjectJson,
+) -> Result<ProjectForUsers, ServiceError> {
+    let mut errors: Vec<FieldError> = vec![];
+    validate_project_name("name", &update.name, &mut errors);
+    if !errors.is_empty() {
+        return Err(ServiceError::Validation(errors));
+    }
+
+    let uuid = parse_uuid(uuid)?;
+    let mut stored = load_own_project(dao, caller, &uuid).await?;
+    let name = update.name.trim();
+    let slug = with_free_slug(dao, name, Some(&uuid), |slug| async move {
+        dao.update_project_name(&uuid, name, &slug).await
+    })
+    .await?;
+
+    stored.project.specs.name = name.to_owned();
+    Ok(to_project_for_users(config, &stored.project, &slug))
+}
+
+pub async fn archive_project(
+    dao: &dyn ProjectDao,
+    caller: &Address,
+    uuid: &str,
+) -> Result<(), ServiceError> {
+    let uuid = parse_uuid(uuid)?;
+    load_own_project(dao, caller, &uuid).await?;
+    dao.archive_project(&uuid).await
+}
+
+/// Loads a project, failing if the caller isn't its creator
+pub async fn load_own_project(
+    dao: &dyn ProjectDao,
+    caller: &Address,
+    uuid: &Uuid,
+) -> Result<StoredProject, ServiceError> {
+    let stored = dao.load_project(uuid).await?;
+    if stored.project.creator != *caller {
+        return Err(ServiceError::Forbidden(
+            "Only the creator can modify the project".to_owned(),
+        ));
+    }
+    Ok(stored)
+}
+
+/// Query parameters of the project listing
+#[derive(Debug, Deserialize)]
+pub struct ListProjectsParams {
+    pub sort: Option<ProjectSort>,
+    pub order: Option<SortOrder>,
+    pub limit: Option<usize>,
+    /// next_cursor of the previous page
+    pub cursor: Option<String>,
+    pub creator: Option<String>,
+    pub token_name: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ProjectsPage {
+    pub projects: Vec<ProjectForUsers>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ProjectsPageJson {
+    pub projects: Vec<ProjectForUsersJson>,
+    pub next_cursor: Option<String>,
+}
+
+impl From<ProjectsPage> for ProjectsPageJson {
+    fn from(page: ProjectsPage) -> Self {
+        ProjectsPageJson {
+            projects: page.projects.into_iter().map(|p| p.into()).collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+/// Newest first, unless specified otherwise
+pub async fn list_projects(
+    dao: &dyn ProjectDao,
+    config: &Config,
+    params: &ListProjectsParams,
+) -> Result<ProjectsPage, ServiceError> {
+    let sort = params.sort.unwrap_or(ProjectSort::CreatedAt);
+    let order = params.order.unwrap_or(match sort {
+        ProjectSort::CreatedAt => SortOrder::Desc,
+        _ => SortOrder::Asc,
+    });
+    let limit = validate_limit(params.limit)?;
+    let creator = match &params.creator {
+        Some(creator) => Some(creator.parse().map_err(|_| {
+            ServiceError::InvalidInput(format!("Invalid creator address: {}", creator))
+        })?),
+        None => None,
+    };
+    let after = match &params.cursor {
+        Some(cursor) => Some(decode_cursor(cursor, sort, order)?),
+        None => None,
+    };
+
+    let page = dao
+        .list_projects(&ProjectQuery {
+            sort,
+            order,
+            limit,
+            after,
+            creator,
+            token_name: params.token_name.clone(),
+        })
+        .await?;
+
+    Ok(ProjectsPage {
+        projects: page
+            .projects
+            .iter()
+            .map(|stored| to_project_for_users(config, &stored.project, &stored.slug))
+            .collect(),
+        next_cursor: page
+            .next_cursor
+            .map(|cursor| encode_cursor(sort, order, cursor)),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchProjectsParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ProjectSearchResult {
+    pub project: ProjectForUsers,
+    /// HTML: the name, escaped, with the matches in <mark> tags
+    pub name_highlight: String,
+    /// HTML, like name_highlight: fragments of the description (markdown source, not rendered)
+    pub description_highlight: Option<String>,
+    pub rank: f32,
+}
+
+#[derive(Serialize)]
+pub struct ProjectSearchResultJson {
+    #[serde(flatten)]
+    pub project: ProjectForUsersJson,
+    pub name_highlight: String,
+    pub description_highlight: Option<String>,
+    pub rank: f32,
 }
 
-pub async fn load_project(dao: &dyn ProjectDao, id: &str) -> Result<Project> {
-    dao.load_project(id.parse()?).await
+impl From<ProjectSearchResult> for ProjectSearchResultJson {
+    fn from(result: ProjectSearchResult) -> Self {
+        ProjectSearchResultJson {
+            project: result.project.into(),
+            name_highlight: result.name_highlight,
+            description_highlight: result.description_highlight,
+            rank: result.rank,
+        }
+    }
 }
 
-pub async fn load_project_with_uuid(dao: &dyn ProjectDao, uuid: &str) -> Result<Project> {
-    dao.load_project_with_uuid(&uuid.parse()?).await
+/// Matches the words in the query as prefixes, in the name, token name and description. Best matches first.
+pub async fn search_projects(
+    dao: &dyn ProjectDao,
+    config: &Config,
+    params: &SearchProjectsParams,
+) -> Result<Vec<ProjectSearchResult>, ServiceError> {
+    let limit = validate_limit(params.limit)?;
+    let tsquery = match to_prefix_tsquery(&params.q) {
+        Some(tsquery) => tsquery,
+        None => return Ok(vec![]),
+    };
+
+    let hits = dao.search_projects(&tsquery, limit).await?;
+
+    Ok(hits
+        .into_iter()
+        .map(|hit| ProjectSearchResult {
+            project: to_project_for_users(config, &hit.project.project, &hit.project.slug),
+            name_highlight: highlight_to_html(&hit.name_highlight),
+            description_highlight: hit.description_highlight.as_deref().map(highlight_to_html),
+            rank: hit.rank,
+        })
+        .collect())
 }
 
-fn to_project_for_users(env: &Env, project_id: &str, project: &Project) -> ProjectForUsers {
+pub fn validate_limit(limit: Option<usize>) -> Result<usize, ServiceError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit == 0 || limit > MAX_PAGE_SIZE {
+        return Err(ServiceError::InvalidInput(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok(limit)
+}
+
+/// The cursor is opaque to clients. It contains the sort, to detect cursors used with a different one.
+#[derive(Debug, Serialize, Deserialize)]
+struct CursorJson {
+    sort: ProjectSort,
+    order: SortOrder,
+    value: String,
+    id: i32,
+}
+
+fn encode_cursor(sort: ProjectSort, order: SortOrder, cursor: ProjectCursor) -> String {
+    let json = CursorJson {
+        sort,
+        order,
+        value: cursor.value,
+        id: cursor.id,
+    };
+    // serializing this struct can't fail
+    BASE64URL_NOPAD.encode(&serde_json::to_vec(&json).unwrap_or_default())
+}
+
+fn decode_cursor(
+    cursor: &str,
+    sort: ProjectSort,
+    order: SortOrder,
+) -> Result<ProjectCursor, ServiceError> {
+    let invalid = || ServiceError::InvalidInput(format!("Invalid cursor: {}", cursor));
+    let bytes = BASE64URL_NOPAD
+        .decode(cursor.as_bytes())
+        .map_err(|_| invalid())?;
+    let json: CursorJson = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+    if json.sort != sort || json.order != order {
+        return Err(ServiceError::InvalidInput(
+            "The cursor belongs to a listing with a different sort".to_owned(),
+        ));
+    }
+    if !is_valid_cursor_value(&json.value, sort) {
+        return Err(invalid());
+    }
+    Ok(ProjectCursor {
+        value: json.value,
+        id: json.id,
+    })
+}
+
+/// The value is cast to the sort column's type in the query: a tampered one would fail it.
+fn is_valid_cursor_value(value: &str, sort: ProjectSort) -> bool {
+    match sort {
+        // Postgres' text output of TIMESTAMPTZ, e.g. 2021-11-30 10:21:07.123456+00
+        ProjectSort::CreatedAt => {
+            DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z").is_ok()
+        }
+        // Postgres doesn't accept NUL in text
+        ProjectSort::Name => !value.contains('\0'),
+        // microalgos
+        ProjectSort::AssetPrice => value.parse::<u64>().is_ok(),
+    }
+}
+
+fn parse_id(id: &str) -> Result<i32, ServiceError> {
+    id.parse()
+        .map_err(|_| ServiceError::InvalidInput(format!("Invalid project id: {}", id)))
+}
+
+pub fn parse_uuid(uuid: &str) -> Result<Uuid, ServiceError> {
+    uuid.parse()
+        .map_err(|_| ServiceError::InvalidInput(format!("Invalid project uuid: {}", uuid)))
+}
+
+/// The uuid is the only public identifier: also used as `id`, which clients still read.
+/// The links use the slug.
+fn to_project_for_users(config: &Config, project: &Project, slug: &str) -> ProjectForUsers {
+    let uuid = project.uuid.to_string();
     ProjectForUsers {
-        id: project_id.to_owned(),
-        uuid: project.uuid.to_string(),
+        id: uuid.clone(),
+        uuid: uuid.clone(),
         name: project.specs.name.clone(),
         asset_price: project.specs.asset_price,
         investors_share: project.specs.investors_share,
@@ -55,9 +537,76 @@ fn to_project_for_users(env: &Env, project_id: &str, project: &Project) -> Proje
         staking_escrow_address: *project.staking_escrow.address(),
         central_escrow_address: *project.central_escrow.address(),
         customer_escrow_address: *project.customer_escrow.address(),
-        invest_link: format!("{}/invest/{}", frontend_host(env), project_id),
-        my_investment_link: format!("{}/investment/{}", frontend_host(env), project_id),
-        project_link: format!("{}/project/{}", frontend_host(env), project_id),
+        invest_link: format!("{}/invest/{}", config.frontend_base_url, slug),
+        my_investment_link: format!("{}/investment/{}", config.frontend_base_url, slug),
+        project_link: format!("{}/project/{}", config.frontend_base_url, slug),
         creator: project.creator,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{decode_cursor, encode_cursor};
+    use crate::{
+        dao::project_dao::{ProjectCursor, ProjectSort, SortOrder},
+        error::ServiceError,
+    };
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_cursor_round_trip() -> Result<()> {
+        let cursor = ProjectCursor {
+            value: "2021-11-30 10:21:07.123456+00".to_owned(),
+            id: 12,
+        };
+        let encoded = encode_cursor(ProjectSort::CreatedAt, SortOrder::Desc, cursor.clone());
+
+        let decoded = decode_cursor(&encoded, ProjectSort::CreatedAt, SortOrder::Desc)?;
+        assert_eq!(cursor, decoded);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_cursor_with_other_sort_is_rejected() -> Result<()> {
+        let cursor = ProjectCursor {
+            value: "my project".to_owned(),
+            id: 12,
+        };
+        let encoded = encode_cursor(ProjectSort::Name, SortOrder::Asc, cursor);
+
+        let res = decode_cursor(&encoded, ProjectSort::Name, SortOrder::Desc);
+        assert!(matches!(res, Err(ServiceError::InvalidInput(_))));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_cursor_with_invalid_value_is_rejected() -> Result<()> {
+        let decode = |sort, value: &str| {
+            let cursor = ProjectCursor {
+                value: value.to_owned(),
+                id: 12,
+            };
+            decode_cursor(
+                &encode_cursor(sort, SortOrder::Asc, cursor),
+                sort,
+                SortOrder::Asc,
+            )
+        };
+
+        assert!(decode(ProjectSort::CreatedAt, "2021-11-30 10:21:07+00").is_ok());
+        assert!(decode(ProjectSort::AssetPrice, "1000000").is_ok());
+        for (sort, value) in [
+            (ProjectSort::CreatedAt, "yesterday"),
+            (ProjectSort::AssetPrice, "1e1000"),
+            (ProjectSort::AssetPrice, "1 OR 1=1"),
+            (ProjectSort::Name, "my\0project"),
+        ] {
+            assert!(matches!(
+                decode(sort, value),
+                Err(ServiceError::InvalidInput(_))
+            ));
+        }
+        Ok(())
+    }
+}
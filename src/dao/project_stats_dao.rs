@@ -0,0 +1,85 @@
+use std::convert::TryFrom;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::db::{get_client, get_u64};
+
+#[async_trait]
+pub trait ProjectStatsDao: Sync + Send {
+    /// Recomputes the statistics of the project from its indexed investments
+    async fn refresh_stats(&self, project_uuid: &Uuid) -> Result<(), ServiceError>;
+    /// Projects whose statistics weren't computed yet get the default (empty) ones
+    async fn load_stats(&self, project_uuid: &Uuid) -> Result<ProjectStats, ServiceError>;
+}
+
+pub struct ProjectStatsDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectStats {
+    pub shares_sold: u64,
+    pub investor_count: u64,
+    pub first_investment_at: Option<DateTime<Utc>>,
+    pub last_investment_at: Option<DateTime<Utc>>,
+    pub shares_sold_last_7_days: u64,
+    /// None if not computed yet
+    pub refreshed_at: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+impl ProjectStatsDao for ProjectStatsDaoImpl {
+    async fn refresh_stats(&self, project_uuid: &Uuid) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO project_stats (project_id, shares_sold, investor_count, first_investment_at, last_investment_at, shares_sold_last_7_days, refreshed_at)
+                SELECT p.id, COALESCE(SUM(i.shares), 0), COUNT(DISTINCT i.investor), MIN(i.round_time), MAX(i.round_time),
+                    COALESCE(SUM(i.shares) FILTER (WHERE i.round_time >= now() - INTERVAL '7 days'), 0), now()
+                FROM project p LEFT JOIN investments i ON i.project_id = p.id
+                WHERE p.uuid=$1::TEXT::UUID
+                GROUP BY p.id
+                ON CONFLICT (project_id) DO UPDATE SET
+                    shares_sold=excluded.shares_sold, investor_count=excluded.investor_count,
+                    first_investment_at=excluded.first_investment_at, last_investment_at=excluded.last_investment_at,
+                    shares_sold_last_7_days=excluded.shares_sold_last_7_days, refreshed_at=excluded.refreshed_at;",
+                &[&project_uuid.to_string()],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_stats(&self, project_uuid: &Uuid) -> Result<ProjectStats, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT s.shares_sold, s.investor_count, s.first_investment_at, s.last_investment_at, s.shares_sold_last_7_days, s.refreshed_at
+                FROM project_stats s JOIN project p ON p.id = s.project_id
+                WHERE p.uuid=$1::TEXT::UUID;",
+                &[&project_uuid.to_string()],
+            )
+            .await?;
+
+        match rows.as_slice() {
+            [row] => Ok(stats_from_row(row)?),
+            _ => Ok(ProjectStats::default()),
+        }
+    }
+}
+
+fn stats_from_row(row: &Row) -> anyhow::Result<ProjectStats> {
+    Ok(ProjectStats {
+        shares_sold: get_u64(row, 0)?,
+        investor_count: u64::try_from(row.get::<_, i32>(1))?,
+        first_investment_at: row.get(2),
+        last_investment_at: row.get(3),
+        shares_sold_last_7_days: get_u64(row, 4)?,
+        refreshed_at: row.get(5),
+    })
+}
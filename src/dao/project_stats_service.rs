@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use core_::flows::create_project::model::Project;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::error::ServiceError;
+
+use super::{
+    project_dao::ProjectDao,
+    project_service::parse_uuid,
+    project_stats_dao::{ProjectStats, ProjectStatsDao},
+    share_holder_service::percentage,
+};
+
+/// Decimal places of the funding velocity
+const VELOCITY_DECIMALS: u32 = 2;
+
+/// Funding progress of a project. Computed from the indexed investments:
+/// can lag behind the chain by a poll interval.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectStatsJson {
+    pub share_count: u64,
+    pub shares_sold: u64,
+    pub shares_available: u64,
+    /// Shares sold, of the share count, e.g. "12.5"
+    pub funded_percentage: Decimal,
+    /// Microalgos: shares sold × asset price
+    pub raised: u64,
+    pub investor_count: u64,
+    pub first_investment_at: Option<DateTime<Utc>>,
+    pub last_investment_at: Option<DateTime<Utc>>,
+    pub shares_sold_last_7_days: u64,
+    /// Funding velocity: average of the last 7 days
+    pub shares_per_day: Decimal,
+    /// None if not computed yet
+    pub refreshed_at: Option<DateTime<Utc>>,
+}
+
+pub async fn load_project_stats(
+    project_dao: &dyn ProjectDao,
+    stats_dao: &dyn ProjectStatsDao,
+    uuid: &str,
+) -> Result<ProjectStatsJson, ServiceError> {
+    let uuid = parse_uuid(uuid)?;
+    let project = project_dao.load_project(&uuid).await?.project;
+    let stats = stats_dao.load_stats(&uuid).await?;
+    Ok(to_project_stats_json(&project, stats))
+}
+
+pub fn to_project_stats_json(project: &Project, stats: ProjectStats) -> ProjectStatsJson {
+    let share_count = project.specs.shares.count;
+    ProjectStatsJson {
+        share_count,
+        shares_sold: stats.shares_sold,
+        shares_available: share_count.saturating_sub(stats.shares_sold),
+        funded_percentage: percentage(stats.shares_sold, share_count),
+        raised: stats
+            .shares_sold
+            .saturating_mul(project.specs.asset_price.0),
+        investor_count: stats.investor_count,
+        first_investment_at: stats.first_investment_at,
+        last_investment_at: stats.last_investment_at,
+        shares_sold_last_7_days: stats.shares_sold_last_7_days,
+        shares_per_day: (Decimal::from(stats.shares_sold_last_7_days) / Decimal::from(7))
+            .round_dp(VELOCITY_DECIMALS)
+            .normalize(),
+        refreshed_at: stats.refreshed_at,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{convert::TryInto, str::FromStr};
+
+    use super::to_project_stats_json;
+    use crate::{dao::project_stats_dao::ProjectStats, test_data::project_json};
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use rust_decimal::Decimal;
+    use tokio::test;
+
+    #[test]
+    async fn test_stats_from_sold_shares() -> Result<()> {
+        // 100 shares at 1 algo
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let stats = ProjectStats {
+            shares_sold: 25,
+            investor_count: 3,
+            shares_sold_last_7_days: 10,
+            ..ProjectStats::default()
+        };
+
+        let json = to_project_stats_json(&project, stats);
+
+        assert_eq!(75, json.shares_available);
+        assert_eq!(Decimal::from(25), json.funded_percentage);
+        assert_eq!(25_000_000, json.raised);
+        assert_eq!(Decimal::from_str("1.43")?, json.shares_per_day);
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ServiceError;
+
+use super::{
+    customer_payment_dao::CustomerPaymentDao,
+    project_dao::ProjectDao,
+    project_service::parse_uuid,
+    time_series::{BucketInterval, TimeBucket},
+};
+
+/// Query parameters of the revenue
+#[derive(Debug, Deserialize)]
+pub struct RevenueParams {
+    /// Day if not set
+    pub interval: Option<BucketInterval>,
+    /// Inclusive, RFC 3339
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive, RFC 3339
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Customer payments to a project
+#[derive(Debug, Serialize)]
+pub struct RevenueJson {
+    /// Microalgos
+    pub total: u64,
+    pub payment_count: u64,
+    pub interval: BucketInterval,
+    /// Oldest first. Intervals without payments are omitted.
+    pub buckets: Vec<RevenueBucketJson>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RevenueBucketJson {
+    pub start: DateTime<Utc>,
+    /// Microalgos
+    pub amount: u64,
+    pub payment_count: u64,
+}
+
+impl From<TimeBucket> for RevenueBucketJson {
+    fn from(bucket: TimeBucket) -> Self {
+        RevenueBucketJson {
+            start: bucket.start,
+            amount: bucket.amount,
+            payment_count: bucket.count,
+        }
+    }
+}
+
+pub async fn load_revenue(
+    project_dao: &dyn ProjectDao,
+    customer_payment_dao: &dyn CustomerPaymentDao,
+    uuid: &str,
+    params: &RevenueParams,
+) -> Result<RevenueJson, ServiceError> {
+    let uuid = parse_uuid(uuid)?;
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from >= to {
+            return Err(ServiceError::InvalidInput(
+                "from has to be before to".to_owned(),
+            ));
+        }
+    }
+    // not found error for unknown projects
+    project_dao.load_project(&uuid).await?;
+
+    let interval = params.interval.unwrap_or(BucketInterval::Day);
+    let buckets = customer_payment_dao
+        .load_revenue(&uuid, interval, params.from, params.to)
+        .await?;
+
+    Ok(RevenueJson {
+        total: buckets.iter().map(|b| b.amount).sum(),
+        payment_count: buckets.iter().map(|b| b.count).sum(),
+        interval,
+        buckets: buckets.into_iter().map(|b| b.into()).collect(),
+    })
+}
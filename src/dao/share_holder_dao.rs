@@ -0,0 +1,192 @@
+use algonaut::core::Address;
+use async_trait::async_trait;
+use deadpool_postgres::Pool;
+use rust_decimal::Decimal;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::{
+    db::{get_address, get_client, get_u64, to_numeric},
+    indexer_cursor::{load_last_round, save_last_round},
+};
+
+const INDEXER_KIND: &str = "share_holders";
+
+#[async_trait]
+pub trait ShareHolderDao: Sync + Send {
+    /// The round as of which the registry of the project was refreshed
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError>;
+    /// Replaces the registry of the project. The free shares are passed, the staked ones
+    /// are computed from the indexed investments and staking events.
+    async fn save_share_holders(
+        &self,
+        project_uuid: &Uuid,
+        free_shares: &[(Address, u64)],
+        round: u64,
+    ) -> Result<(), ServiceError>;
+    /// Largest holders first
+    async fn load_share_holders(
+        &self,
+        project_uuid: &Uuid,
+        limit: usize,
+        after: Option<&ShareHolderCursor>,
+    ) -> Result<ShareHolderPage, ServiceError>;
+}
+
+pub struct ShareHolderDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareHolder {
+    pub address: Address,
+    pub free_shares: u64,
+    pub staked_shares: u64,
+}
+
+/// Position of the last holder of a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareHolderCursor {
+    pub total_shares: u64,
+    pub address: String,
+}
+
+#[derive(Debug)]
+pub struct ShareHolderPage {
+    pub holders: Vec<ShareHolder>,
+    /// None if this is the last page
+    pub next_cursor: Option<ShareHolderCursor>,
+}
+
+#[async_trait]
+impl ShareHolderDao for ShareHolderDaoImpl {
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        load_last_round(&client, project_uuid, INDEXER_KIND).await
+    }
+
+    async fn save_share_holders(
+        &self,
+        project_uuid: &Uuid,
+        free_shares: &[(Address, u64)],
+        round: u64,
+    ) -> Result<(), ServiceError> {
+        let addresses: Vec<String> = free_shares.iter().map(|(a, _)| a.to_string()).collect();
+        let amounts: Vec<Decimal> = free_shares.iter().map(|(_, s)| to_numeric(*s)).collect();
+
+        let mut client = get_client(&self.pool).await?;
+        let tx = client.transaction().await?;
+        let project_id: i32 = tx
+            .query_one(
+                "SELECT id FROM project WHERE uuid=$1::TEXT::UUID;",
+                &[&project_uuid.to_string()],
+            )
+            .await?
+            .get(0);
+
+        tx.execute(
+            "DELETE FROM share_holders WHERE project_id=$1;",
+            &[&project_id],
+        )
+        .await?;
+        tx.execute(
+            "INSERT INTO share_holders (project_id, address, free_shares)
+            SELECT $1, address, amount FROM UNNEST($2::TEXT[], $3::NUMERIC[]) AS b(address, amount);",
+            &[&project_id, &addresses, &amounts],
+        )
+        .await?;
+        // investments stake the shares
+        tx.execute(
+            "INSERT INTO share_holders (project_id, address, staked_shares)
+            SELECT $1, investor, SUM(amount) FROM (
+                SELECT investor, shares AS amount FROM investments WHERE project_id=$1
+                UNION ALL
+                SELECT investor, CASE kind WHEN 'unstake' THEN -amount ELSE amount END FROM staking_events
+                    WHERE project_id=$1 AND kind IN ('stake', 'unstake')
+            ) e
+            GROUP BY investor HAVING SUM(amount) > 0
+            ON CONFLICT (project_id, address) DO UPDATE SET staked_shares=excluded.staked_shares;",
+            &[&project_id],
+        )
+        .await?;
+        save_last_round(&tx, project_uuid, INDEXER_KIND, round).await?;
+        tx.commit().await?;
+
+        log::debug!(
+            "Saved share holders for project: {}, as of round: {}",
+            project_uuid,
+            round
+        );
+        Ok(())
+    }
+
+    async fn load_share_holders(
+        &self,
+        project_uuid: &Uuid,
+        limit: usize,
+        after: Option<&ShareHolderCursor>,
+    ) -> Result<ShareHolderPage, ServiceError> {
+        // fetch one more to know whether there's a next page
+        let sql_limit = (limit + 1) as i64;
+        let uuid = project_uuid.to_string();
+        let client = get_client(&self.pool).await?;
+        let rows = match after {
+            Some(after) => {
+                client
+                    .query(
+                        "SELECT h.address, h.free_shares, h.staked_shares, h.total_shares
+                        FROM share_holders h JOIN project p ON p.id = h.project_id
+                        WHERE p.uuid=$1::TEXT::UUID
+                            AND (h.total_shares < $2 OR (h.total_shares = $2 AND h.address > $3))
+                        ORDER BY h.total_shares DESC, h.address LIMIT $4;",
+                        &[
+                            &uuid,
+                            &to_numeric(after.total_shares),
+                            &after.address,
+                            &sql_limit,
+                        ],
+                    )
+                    .await?
+            }
+            None => {
+                client
+                    .query(
+                        "SELECT h.address, h.free_shares, h.staked_shares, h.total_shares
+                        FROM share_holders h JOIN project p ON p.id = h.project_id
+                        WHERE p.uuid=$1::TEXT::UUID
+                        ORDER BY h.total_shares DESC, h.address LIMIT $2;",
+                        &[&uuid, &sql_limit],
+                    )
+                    .await?
+            }
+        };
+
+        let mut holders = vec![];
+        let mut next_cursor = None;
+        for row in rows.iter().take(limit) {
+            next_cursor = Some(ShareHolderCursor {
+                total_shares: get_u64(row, 3)?,
+                address: row.get(0),
+            });
+            holders.push(share_holder_from_row(row)?);
+        }
+        if rows.len() <= limit {
+            next_cursor = None;
+        }
+
+        Ok(ShareHolderPage {
+            holders,
+            next_cursor,
+        })
+    }
+}
+
+fn share_holder_from_row(row: &Row) -> anyhow::Result<ShareHolder> {
+    Ok(ShareHolder {
+        address: get_address(row, 0)?,
+        free_shares: get_u64(row, 1)?,
+        staked_shares: get_u64(row, 2)?,
+    })
+}
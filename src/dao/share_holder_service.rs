@@ -0,0 +1,145 @@
+use data_encoding::BASE64URL_NOPAD;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ServiceError;
+
+use super::{
+    project_dao::ProjectDao,
+    project_service::{parse_uuid, validate_limit},
+    share_holder_dao::{ShareHolder, ShareHolderCursor, ShareHolderDao},
+};
+
+/// Decimal places of the ownership percentages
+const PERCENTAGE_DECIMALS: u32 = 4;
+
+/// Query parameters of the share holders listing
+#[derive(Debug, Deserialize)]
+pub struct ShareHoldersParams {
+    pub limit: Option<usize>,
+    /// next_cursor of the previous page
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareHoldersPageJson {
+    pub share_count: u64,
+    /// The round as of which the registry was refreshed, None if it wasn't yet
+    pub round: Option<u64>,
+    /// Largest holders first
+    pub holders: Vec<ShareHolderJson>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareHolderJson {
+    pub address: String,
+    /// In the holder's account
+    pub free_shares: u64,
+    /// In the staking escrow
+    pub staked_shares: u64,
+    pub total_shares: u64,
+    /// Of the project's share count, e.g. "12.5"
+    pub percentage: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CursorJson {
+    total_shares: u64,
+    address: String,
+}
+
+pub async fn load_share_holders(
+    project_dao: &dyn ProjectDao,
+    share_holder_dao: &dyn ShareHolderDao,
+    uuid: &str,
+    params: &ShareHoldersParams,
+) -> Result<ShareHoldersPageJson, ServiceError> {
+    let limit = validate_limit(params.limit)?;
+    let after = params.cursor.as_deref().map(decode_cursor).transpose()?;
+    let uuid = parse_uuid(uuid)?;
+    let share_count = project_dao
+        .load_project(&uuid)
+        .await?
+        .project
+        .specs
+        .shares
+        .count;
+
+    let round = share_holder_dao.last_indexed_round(&uuid).await?;
+    let page = share_holder_dao
+        .load_share_holders(&uuid, limit, after.as_ref())
+        .await?;
+
+    Ok(ShareHoldersPageJson {
+        share_count,
+        round,
+        holders: page
+            .holders
+            .into_iter()
+            .map(|holder| to_share_holder_json(holder, share_count))
+            .collect(),
+        next_cursor: page.next_cursor.map(encode_cursor),
+    })
+}
+
+fn to_share_holder_json(holder: ShareHolder, share_count: u64) -> ShareHolderJson {
+    let total_shares = holder.free_shares + holder.staked_shares;
+    ShareHolderJson {
+        address: holder.address.to_string(),
+        free_shares: holder.free_shares,
+        staked_shares: holder.staked_shares,
+        total_shares,
+        percentage: percentage(total_shares, share_count),
+    }
+}
+
+/// Of the share count, rounded
+pub fn percentage(shares: u64, share_count: u64) -> Decimal {
+    if share_count == 0 {
+        return Decimal::ZERO;
+    }
+    (Decimal::from(shares) * Decimal::from(100) / Decimal::from(share_count))
+        .round_dp(PERCENTAGE_DECIMALS)
+        .normalize()
+}
+
+fn encode_cursor(cursor: ShareHolderCursor) -> String {
+    let json = CursorJson {
+        total_shares: cursor.total_shares,
+        address: cursor.address,
+    };
+    // serializing this struct can't fail
+    BASE64URL_NOPAD.encode(&serde_json::to_vec(&json).unwrap_or_default())
+}
+
+fn decode_cursor(cursor: &str) -> Result<ShareHolderCursor, ServiceError> {
+    let invalid = || ServiceError::InvalidInput(format!("Invalid cursor: {}", cursor));
+    let bytes = BASE64URL_NOPAD
+        .decode(cursor.as_bytes())
+        .map_err(|_| invalid())?;
+    let json: CursorJson = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+    Ok(ShareHolderCursor {
+        total_shares: json.total_shares,
+        address: json.address,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::percentage;
+    use anyhow::Result;
+    use rust_decimal::Decimal;
+    use tokio::test;
+
+    #[test]
+    async fn test_percentage() -> Result<()> {
+        assert_eq!(Decimal::from_str("12.5")?, percentage(125, 1000));
+        assert_eq!(Decimal::from_str("33.3333")?, percentage(1, 3));
+        assert_eq!(Decimal::from(100), percentage(3, 3));
+        assert_eq!(Decimal::ZERO, percentage(0, 0));
+        Ok(())
+    }
+}
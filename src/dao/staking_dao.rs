@@ -0,0 +1,211 @@
+use algonaut::core::Address;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::{
+    db::{get_client, get_id, get_u64, to_bigint, to_numeric},
+    indexer_cursor::{load_last_round, row_param, save_indexed_txs, IndexedTxTable},
+};
+
+const TABLE: IndexedTxTable = IndexedTxTable {
+    name: "staking_events",
+    columns: &["txid", "investor", "kind", "amount", "round", "round_time"],
+};
+
+#[async_trait]
+pub trait StakingDao: Sync + Send {
+    /// The last round up to which the staking events of the project were indexed
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError>;
+    /// Saves the staking events indexed up to `round`, see indexer_cursor::save_indexed_txs
+    async fn save_staking_events(
+        &self,
+        project_uuid: &Uuid,
+        events: &[StakingEvent],
+        round: u64,
+    ) -> Result<(), ServiceError>;
+    /// Totals of the investor's investments and staking events in the project
+    async fn load_investor_totals(
+        &self,
+        project_uuid: &Uuid,
+        investor: &Address,
+    ) -> Result<InvestorTotals, ServiceError>;
+    /// The investor's investments and staking events in the project, newest first
+    async fn load_investor_history(
+        &self,
+        project_uuid: &Uuid,
+        investor: &Address,
+    ) -> Result<Vec<InvestorEvent>, ServiceError>;
+}
+
+pub struct StakingDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvestorEventKind {
+    /// Buying shares. They're staked with the investment.
+    /// Not a staking event: investments are indexed on their own (see investment_dao).
+    Invest,
+    Stake,
+    Unstake,
+    /// Claiming dividends
+    Harvest,
+}
+
+impl InvestorEventKind {
+    const ALL: [InvestorEventKind; 4] = [
+        InvestorEventKind::Invest,
+        InvestorEventKind::Stake,
+        InvestorEventKind::Unstake,
+        InvestorEventKind::Harvest,
+    ];
+
+    /// Stored value, same as the json one
+    fn as_str(&self) -> &'static str {
+        match self {
+            InvestorEventKind::Invest => "invest",
+            InvestorEventKind::Stake => "stake",
+            InvestorEventKind::Unstake => "unstake",
+            InvestorEventKind::Harvest => "harvest",
+        }
+    }
+
+    fn parse(str: &str) -> anyhow::Result<InvestorEventKind> {
+        InvestorEventKind::ALL
+            .iter()
+            .find(|k| k.as_str() == str)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown investor event kind: {}", str))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakingEvent {
+    pub txid: String,
+    pub investor: Address,
+    /// Stake, unstake or harvest
+    pub kind: InvestorEventKind,
+    /// Shares, or microalgos for harvests
+    pub amount: u64,
+    pub round: u64,
+    pub round_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvestorEvent {
+    pub txid: String,
+    pub kind: InvestorEventKind,
+    /// Shares, or microalgos for harvests
+    pub amount: u64,
+    pub round: u64,
+    pub round_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InvestorTotals {
+    pub invested_shares: u64,
+    pub staked_shares: u64,
+    pub unstaked_shares: u64,
+    /// Microalgos
+    pub harvested: u64,
+}
+
+#[async_trait]
+impl StakingDao for StakingDaoImpl {
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        load_last_round(&client, project_uuid, TABLE.name).await
+    }
+
+    async fn save_staking_events(
+        &self,
+        project_uuid: &Uuid,
+        events: &[StakingEvent],
+        round: u64,
+    ) -> Result<(), ServiceError> {
+        save_indexed_txs(&self.pool, &TABLE, project_uuid, events, round, |event| {
+            Ok(vec![
+                row_param(event.txid.clone()),
+                row_param(event.investor.to_string()),
+                row_param(event.kind.as_str()),
+                row_param(to_numeric(event.amount)),
+                row_param(to_bigint(event.round)?),
+                row_param(event.round_time),
+            ])
+        })
+        .await
+    }
+
+    async fn load_investor_totals(
+        &self,
+        project_uuid: &Uuid,
+        investor: &Address,
+    ) -> Result<InvestorTotals, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "WITH p AS (SELECT id FROM project WHERE uuid=$1::TEXT::UUID),
+                e AS (SELECT kind, amount FROM staking_events WHERE project_id=(SELECT id FROM p) AND investor=$2)
+                SELECT
+                    (SELECT COALESCE(SUM(shares), 0) FROM investments WHERE project_id=(SELECT id FROM p) AND investor=$2),
+                    (SELECT COALESCE(SUM(amount), 0) FROM e WHERE kind='stake'),
+                    (SELECT COALESCE(SUM(amount), 0) FROM e WHERE kind='unstake'),
+                    (SELECT COALESCE(SUM(amount), 0) FROM e WHERE kind='harvest');",
+                &[&project_uuid.to_string(), &investor.to_string()],
+            )
+            .await?;
+
+        match rows.as_slice() {
+            [row] => Ok(InvestorTotals {
+                invested_shares: get_u64(row, 0)?,
+                staked_shares: get_u64(row, 1)?,
+                unstaked_shares: get_u64(row, 2)?,
+                harvested: get_u64(row, 3)?,
+            }),
+            _ => Err(anyhow!("Unexpected row count: {}", rows.len()).into()),
+        }
+    }
+
+    async fn load_investor_history(
+        &self,
+        project_uuid: &Uuid,
+        investor: &Address,
+    ) -> Result<Vec<InvestorEvent>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "WITH p AS (SELECT id FROM project WHERE uuid=$1::TEXT::UUID)
+                SELECT txid, 'invest', shares, round, round_time FROM investments
+                    WHERE project_id=(SELECT id FROM p) AND investor=$2
+                UNION ALL
+                SELECT txid, kind, amount, round, round_time FROM staking_events
+                    WHERE project_id=(SELECT id FROM p) AND investor=$2
+                ORDER BY round DESC, txid;",
+                &[&project_uuid.to_string(), &investor.to_string()],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(investor_event_from_row)
+            .collect::<anyhow::Result<Vec<_>>>()?)
+    }
+}
+
+fn investor_event_from_row(row: &Row) -> anyhow::Result<InvestorEvent> {
+    Ok(InvestorEvent {
+        txid: row.get(0),
+        kind: InvestorEventKind::parse(row.get(1))?,
+        amount: get_u64(row, 2)?,
+        round: get_id(row, 3)?,
+        round_time: row.get(4),
+    })
+}
@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+
+use algonaut::core::Address;
+use chrono::{DateTime, Utc};
+use core_::flows::create_project::model::Project;
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::{
+        signed_tx::{decode_signed_tx, SignedTx, TxSignature},
+        tx_submitter::TxSubmitter,
+    },
+    error::ServiceError,
+};
+
+use super::{
+    project_dao::ProjectDao,
+    project_service::parse_uuid,
+    submitted_tx_dao::{
+        NewSubmittedTx, StatusUpdate, SubmissionStatus, SubmittedTx, SubmittedTxDao,
+    },
+    withdrawal_service::parse_txid,
+};
+
+/// Max transactions in a group (protocol limit)
+const MAX_GROUP_SIZE: usize = 16;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubmitTxsJson {
+    /// The project the transactions belong to: at least one has to involve its escrows or app,
+    /// and its escrows are the only logic signed senders accepted
+    pub project_uuid: String,
+    /// Base64 msgpack of the signed transactions (a group, in order)
+    pub txs: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmittedTxsJson {
+    /// In the order of the submitted transactions
+    pub txids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmittedTxJson {
+    pub txid: String,
+    pub project_uuid: String,
+    /// Base64
+    pub group_id: Option<String>,
+    pub status: SubmissionStatus,
+    pub confirmed_round: Option<u64>,
+    /// Why it was rejected
+    pub error: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<SubmittedTx> for SubmittedTxJson {
+    fn from(tx: SubmittedTx) -> Self {
+        SubmittedTxJson {
+            txid: tx.txid,
+            project_uuid: tx.project_uuid.to_string(),
+            group_id: tx.group_id,
+            status: tx.status,
+            confirmed_round: tx.confirmed_round,
+            error: tx.error,
+            submitted_at: tx.submitted_at,
+            updated_at: tx.updated_at,
+        }
+    }
+}
+
+/// Relays the transactions to algod. They're tracked (see relay) until confirmed, rejected or expired.
+pub async fn submit_txs(
+    project_dao: &dyn ProjectDao,
+    submitted_tx_dao: &dyn SubmittedTxDao,
+    submitter: &dyn TxSubmitter,
+    body: &SubmitTxsJson,
+) -> Result<SubmittedTxsJson, ServiceError> {
+    let uuid = parse_uuid(&body.project_uuid)?;
+    let bytes = body
+        .txs
+        .iter()
+        .map(|tx| {
+            BASE64
+                .decode(tx.as_bytes())
+                .map_err(|_| ServiceError::InvalidInput("Invalid base64 transaction".to_owned()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let txs = bytes
+        .iter()
+        .map(|bytes| decode_signed_tx(bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let project = project_dao.load_project(&uuid).await?.project;
+    validate_group(&project, &txs)?;
+
+    let submitted: Vec<NewSubmittedTx> = txs
+        .iter()
+        .map(|tx| NewSubmittedTx {
+            txid: tx.txid.clone(),
+            group_id: tx.group.as_ref().map(|group| BASE64.encode(group)),
+            last_valid: tx.last_valid,
+        })
+        .collect();
+    // saved first: if it was saved after, a failure would leave the submitted transactions untracked
+    submitted_tx_dao
+        .save_submitted_txs(&uuid, &submitted)
+        .await?;
+
+    match submitter.submit(&bytes.concat()).await {
+        Ok(()) => {}
+        // may still be confirmed: left pending, the tracking settles them
+        Err(e @ ServiceError::Upstream(_)) => return Err(e),
+        Err(e) => {
+            let update = StatusUpdate {
+                status: SubmissionStatus::Rejected,
+                confirmed_round: None,
+                error: Some(e.to_string()),
+            };
+            for tx in &submitted {
+                submitted_tx_dao.update_status(&tx.txid, &update).await?;
+            }
+            return Err(e);
+        }
+    }
+
+    Ok(SubmittedTxsJson {
+        txids: submitted.into_iter().map(|tx| tx.txid).collect(),
+    })
+}
+
+pub async fn load_submitted_tx(
+    submitted_tx_dao: &dyn SubmittedTxDao,
+    txid: &str,
+) -> Result<SubmittedTxJson, ServiceError> {
+    let txid = parse_txid(txid)?;
+    Ok(submitted_tx_dao.load_submitted_tx(&txid).await?.into())
+}
+
+/// Structural checks, failing early on what algod would reject or what doesn't belong to the project.
+/// The node does the rest (signatures, logic, balances).
+fn validate_group(project: &Project, txs: &[SignedTx]) -> Result<(), ServiceError> {
+    let invalid = |msg: String| Err(ServiceError::InvalidInput(msg));
+
+    if txs.is_empty() || txs.len() > MAX_GROUP_SIZE {
+        return invalid(format!(
+            "Expected 1 to {} transactions, got: {}",
+            MAX_GROUP_SIZE,
+            txs.len()
+        ));
+    }
+    if txs.len() > 1 {
+        let group = &txs[0].group;
+        if group.is_none() || txs.iter().any(|tx| tx.group != *group) {
+            return invalid("The transactions don't have the same group id".to_owned());
+        }
+    }
+    let mut txids = HashSet::new();
+    if !txs.iter().all(|tx| txids.insert(tx.txid.as_str())) {
+        return invalid("Duplicate transaction".to_owned());
+    }
+
+    let escrows = [
+        &project.invest_escrow,
+        &project.staking_escrow,
+        &project.central_escrow,
+        &project.customer_escrow,
+    ];
+    let is_escrow = |address: &Address| escrows.iter().any(|escrow| escrow.address() == address);
+    let involves_project = txs.iter().any(|tx| {
+        is_escrow(&tx.sender)
+            || tx.receiver.as_ref().map(is_escrow).unwrap_or(false)
+            || tx.app_id == Some(project.central_app_id)
+    });
+    if !involves_project {
+        return invalid(
+            "None of the transactions involves the project's escrows or app".to_owned(),
+        );
+    }
+    for (index, tx) in txs.iter().enumerate() {
+        if let TxSignature::Logic(program) = &tx.signature {
+            let is_project_escrow = escrows
+                .iter()
+                .any(|escrow| *escrow.address() == tx.sender && escrow.program.0 == *program);
+            if !is_project_escrow {
+                return invalid(format!(
+                    "Transaction {}: logic signature of an account that's not an escrow of the project: {}",
+                    index, tx.sender
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::validate_group;
+    use crate::{
+        chain::signed_tx::{SignedTx, TxSignature},
+        error::ServiceError,
+        test_data::project_json,
+    };
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_validate_group() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let user_tx = SignedTx {
+            txid: "A".to_owned(),
+            sender: project.creator,
+            receiver: None,
+            app_id: Some(project.central_app_id),
+            group: Some(vec![1; 32]),
+            last_valid: 2000,
+            signature: TxSignature::Single,
+        };
+        let escrow_tx = SignedTx {
+            txid: "B".to_owned(),
+            sender: *project.invest_escrow.address(),
+            receiver: Some(project.creator),
+            app_id: None,
+            signature: TxSignature::Logic(project.invest_escrow.program.0.clone()),
+            ..user_tx.clone()
+        };
+        validate_group(&project, &[user_tx.clone(), escrow_tx.clone()])?;
+
+        let other_group = SignedTx {
+            group: Some(vec![2; 32]),
+            ..escrow_tx.clone()
+        };
+        assert_invalid(validate_group(&project, &[user_tx.clone(), other_group]));
+
+        // the program of another escrow
+        let other_program = SignedTx {
+            signature: TxSignature::Logic(project.central_escrow.program.0.clone()),
+            ..escrow_tx
+        };
+        assert_invalid(validate_group(&project, &[user_tx.clone(), other_program]));
+
+        assert_invalid(validate_group(
+            &project,
+            &[user_tx.clone(), user_tx.clone()],
+        ));
+
+        // a payment to the customer escrow on its own
+        let payment = SignedTx {
+            receiver: Some(*project.customer_escrow.address()),
+            app_id: None,
+            group: None,
+            ..user_tx.clone()
+        };
+        validate_group(&project, &[payment.clone()])?;
+        // nothing to do with the project
+        let unrelated = SignedTx {
+            receiver: Some(project.creator),
+            ..payment
+        };
+        assert_invalid(validate_group(&project, &[unrelated]));
+        let other_app = SignedTx {
+            app_id: Some(project.central_app_id + 1),
+            group: None,
+            ..user_tx
+        };
+        assert_invalid(validate_group(&project, &[other_app]));
+
+        assert_invalid(validate_group(&project, &[]));
+        Ok(())
+    }
+
+    fn assert_invalid(res: Result<(), ServiceError>) {
+        assert!(matches!(res, Err(ServiceError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,204 @@
+use std::convert::TryFrom;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use serde::Serialize;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::db::{get_client, get_id, get_uuid, to_bigint};
+
+#[async_trait]
+pub trait SubmittedTxDao: Sync + Send {
+    /// Saves the transactions as pending. Conflict if one was already submitted, unless it was rejected.
+    async fn save_submitted_txs(
+        &self,
+        project_uuid: &Uuid,
+        txs: &[NewSubmittedTx],
+    ) -> Result<(), ServiceError>;
+    async fn update_status(&self, txid: &str, update: &StatusUpdate) -> Result<(), ServiceError>;
+    async fn load_submitted_tx(&self, txid: &str) -> Result<SubmittedTx, ServiceError>;
+    /// Oldest first
+    async fn load_pending_txs(&self) -> Result<Vec<SubmittedTx>, ServiceError>;
+}
+
+pub struct SubmittedTxDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    /// Submitted, waiting for confirmation
+    Pending,
+    Confirmed,
+    /// By algod, when submitting or later (removed from the pool)
+    Rejected,
+    /// Not confirmed until its last valid round
+    Expired,
+}
+
+impl SubmissionStatus {
+    const ALL: [SubmissionStatus; 4] = [
+        SubmissionStatus::Pending,
+        SubmissionStatus::Confirmed,
+        SubmissionStatus::Rejected,
+        SubmissionStatus::Expired,
+    ];
+
+    /// Stored value, same as the json one
+    fn as_str(&self) -> &'static str {
+        match self {
+            SubmissionStatus::Pending => "pending",
+            SubmissionStatus::Confirmed => "confirmed",
+            SubmissionStatus::Rejected => "rejected",
+            SubmissionStatus::Expired => "expired",
+        }
+    }
+
+    fn parse(str: &str) -> anyhow::Result<SubmissionStatus> {
+        SubmissionStatus::ALL
+            .iter()
+            .find(|s| s.as_str() == str)
+            .copied()
+            .ok_or_else(|| anyhow!("Unknown submission status: {}", str))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewSubmittedTx {
+    pub txid: String,
+    /// Base64
+    pub group_id: Option<String>,
+    pub last_valid: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusUpdate {
+    pub status: SubmissionStatus,
+    pub confirmed_round: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmittedTx {
+    pub txid: String,
+    pub project_uuid: Uuid,
+    /// Base64
+    pub group_id: Option<String>,
+    pub status: SubmissionStatus,
+    pub last_valid: u64,
+    pub confirmed_round: Option<u64>,
+    pub error: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[async_trait]
+impl SubmittedTxDao for SubmittedTxDaoImpl {
+    async fn save_submitted_txs(
+        &self,
+        project_uuid: &Uuid,
+        txs: &[NewSubmittedTx],
+    ) -> Result<(), ServiceError> {
+        let mut client = get_client(&self.pool).await?;
+        let tx = client.transaction().await?;
+        for submitted in txs {
+            // rejected transactions can be submitted again, e.g. after funding the sender
+            let updated = tx
+                .execute(
+                    "INSERT INTO submitted_txs (txid, project_id, group_id, status, last_valid)
+                    SELECT $1, id, $3, $4, $5 FROM project WHERE uuid=$2::TEXT::UUID
+                    ON CONFLICT (txid) DO UPDATE SET status=excluded.status, error=NULL, submitted_at=now(), updated_at=now()
+                    WHERE submitted_txs.status=$6;",
+                    &[
+                        &submitted.txid,
+                        &project_uuid.to_string(),
+                        &submitted.group_id,
+                        &SubmissionStatus::Pending.as_str(),
+                        &to_bigint(submitted.last_valid)?,
+                        &SubmissionStatus::Rejected.as_str(),
+                    ],
+                )
+                .await?;
+            if updated != 1 {
+                return Err(ServiceError::Conflict(format!(
+                    "Transaction already submitted: {}",
+                    submitted.txid
+                )));
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_status(&self, txid: &str, update: &StatusUpdate) -> Result<(), ServiceError> {
+        let confirmed_round = update.confirmed_round.map(to_bigint).transpose()?;
+        let client = get_client(&self.pool).await?;
+        client
+            .execute(
+                "UPDATE submitted_txs SET status=$2, confirmed_round=$3, error=$4, updated_at=now() WHERE txid=$1;",
+                &[&txid, &update.status.as_str(), &confirmed_round, &update.error],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_submitted_tx(&self, txid: &str) -> Result<SubmittedTx, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT s.txid, p.uuid::TEXT, s.group_id, s.status, s.last_valid, s.confirmed_round, s.error, s.submitted_at, s.updated_at
+                FROM submitted_txs s JOIN project p ON p.id = s.project_id
+                WHERE s.txid=$1;",
+                &[&txid],
+            )
+            .await?;
+
+        match rows.as_slice() {
+            [row] => Ok(submitted_tx_from_row(row)?),
+            _ => Err(ServiceError::NotFound(format!(
+                "Transaction not found: {}",
+                txid
+            ))),
+        }
+    }
+
+    async fn load_pending_txs(&self) -> Result<Vec<SubmittedTx>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "SELECT s.txid, p.uuid::TEXT, s.group_id, s.status, s.last_valid, s.confirmed_round, s.error, s.submitted_at, s.updated_at
+                FROM submitted_txs s JOIN project p ON p.id = s.project_id
+                WHERE s.status=$1 ORDER BY s.submitted_at;",
+                &[&SubmissionStatus::Pending.as_str()],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(submitted_tx_from_row)
+            .collect::<anyhow::Result<Vec<_>>>()?)
+    }
+}
+
+fn submitted_tx_from_row(row: &Row) -> anyhow::Result<SubmittedTx> {
+    Ok(SubmittedTx {
+        txid: row.get(0),
+        project_uuid: get_uuid(row, 1)?,
+        group_id: row.get(2),
+        status: SubmissionStatus::parse(row.get(3))?,
+        last_valid: get_id(row, 4)?,
+        confirmed_round: row
+            .get::<_, Option<i64>>(5)
+            .map(u64::try_from)
+            .transpose()?,
+        error: row.get(6),
+        submitted_at: row.get(7),
+        updated_at: row.get(8),
+    })
+}
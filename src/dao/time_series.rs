@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Time buckets start at 00:00 UTC. Weeks start on Monday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BucketInterval {
+    Day,
+    Week,
+    Month,
+}
+
+impl BucketInterval {
+    /// For postgres' date_trunc
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BucketInterval::Day => "day",
+            BucketInterval::Week => "week",
+            BucketInterval::Month => "month",
+        }
+    }
+}
+
+/// Aggregated amounts in a time bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeBucket {
+    pub start: DateTime<Utc>,
+    pub amount: u64,
+    pub count: u64,
+}
@@ -0,0 +1,114 @@
+use algonaut::{
+    algod::v2::Algod,
+    core::{Address, MicroAlgos, ToMsgPack},
+};
+use anyhow::Error;
+use data_encoding::BASE64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::transactions::{self, GroupTx},
+    error::ServiceError,
+};
+
+use super::{project_dao::ProjectDao, project_service::parse_uuid};
+
+/// The operations we build transaction groups for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxAction {
+    Invest,
+    Stake,
+    Unstake,
+    Harvest,
+    /// A customer paying the project
+    Pay,
+}
+
+impl TxAction {
+    /// From the path segment
+    pub fn parse(str: &str) -> Result<TxAction, ServiceError> {
+        match str {
+            "invest" => Ok(TxAction::Invest),
+            "stake" => Ok(TxAction::Stake),
+            "unstake" => Ok(TxAction::Unstake),
+            "harvest" => Ok(TxAction::Harvest),
+            "pay" => Ok(TxAction::Pay),
+            _ => Err(ServiceError::NotFound(format!("Unknown action: {}", str))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildTxsJson {
+    /// The user that signs (and pays the fees)
+    pub address: String,
+    /// Shares for invest, stake and unstake, microalgos for harvest and pay
+    pub amount: u64,
+}
+
+/// To be signed (the unsigned transactions), and submitted in this order
+#[derive(Debug, Serialize)]
+pub struct TxGroupJson {
+    pub txs: Vec<TxJson>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TxJson {
+    /// Whether `tx` is signed (by an escrow, with its logic signature).
+    /// Otherwise it's to be signed by the user.
+    pub signed: bool,
+    /// Base64 msgpack of the (signed) transaction
+    pub tx: String,
+}
+
+pub async fn build_txs(
+    project_dao: &dyn ProjectDao,
+    algod: &Algod,
+    uuid: &str,
+    action: &str,
+    body: &BuildTxsJson,
+) -> Result<TxGroupJson, ServiceError> {
+    let action = TxAction::parse(action)?;
+    let user: Address = body
+        .address
+        .parse()
+        .map_err(|_| ServiceError::InvalidInput(format!("Invalid address: {}", body.address)))?;
+    if body.amount == 0 {
+        return Err(ServiceError::InvalidInput(
+            "Amount must be greater than 0".to_owned(),
+        ));
+    }
+    let uuid = parse_uuid(uuid)?;
+    let project = project_dao.load_project(&uuid).await?.project;
+
+    let txs = match action {
+        TxAction::Invest => transactions::invest_txs(algod, &project, &user, body.amount).await?,
+        TxAction::Stake => transactions::stake_txs(algod, &project, &user, body.amount).await?,
+        TxAction::Unstake => transactions::unstake_txs(algod, &project, &user, body.amount).await?,
+        TxAction::Harvest => {
+            transactions::harvest_txs(algod, &project, &user, MicroAlgos(body.amount)).await?
+        }
+        TxAction::Pay => {
+            transactions::pay_txs(algod, &project, &user, MicroAlgos(body.amount)).await?
+        }
+    };
+
+    Ok(TxGroupJson {
+        txs: txs
+            .into_iter()
+            .map(to_tx_json)
+            .collect::<Result<Vec<_>, _>>()?,
+    })
+}
+
+fn to_tx_json(tx: GroupTx) -> Result<TxJson, ServiceError> {
+    let (signed, bytes) = match tx {
+        GroupTx::Unsigned(tx) => (false, tx.to_msg_pack()),
+        GroupTx::Signed(tx) => (true, tx.to_msg_pack()),
+    };
+    Ok(TxJson {
+        signed,
+        tx: BASE64.encode(&bytes.map_err(Error::from)?),
+    })
+}
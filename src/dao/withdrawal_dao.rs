@@ -0,0 +1,159 @@
+use algonaut::core::{Address, MicroAlgos};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+use crate::error::ServiceError;
+
+use super::{
+    db::{get_address, get_client, get_id, get_microalgos, to_bigint, to_numeric},
+    indexer_cursor::{load_last_round, row_param, save_indexed_txs, IndexedTxTable},
+};
+
+const TABLE: IndexedTxTable = IndexedTxTable {
+    name: "withdrawals",
+    columns: &["txid", "receiver", "amount", "round", "round_time"],
+};
+
+#[async_trait]
+pub trait WithdrawalDao: Sync + Send {
+    /// The last round up to which the withdrawals of the project were indexed
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError>;
+    /// Saves the withdrawals indexed up to `round`, see indexer_cursor::save_indexed_txs
+    async fn save_withdrawals(
+        &self,
+        project_uuid: &Uuid,
+        withdrawals: &[Withdrawal],
+        round: u64,
+    ) -> Result<(), ServiceError>;
+    /// Creates or replaces the description of the withdrawal, which doesn't have to be indexed yet.
+    async fn save_description(
+        &self,
+        project_uuid: &Uuid,
+        txid: &str,
+        description: &str,
+    ) -> Result<(), ServiceError>;
+    /// The indexed withdrawals with their descriptions and the described ones not indexed yet.
+    /// Not indexed first, then newest first.
+    async fn load_withdrawals(
+        &self,
+        project_uuid: &Uuid,
+    ) -> Result<Vec<WithdrawalRecord>, ServiceError>;
+}
+
+pub struct WithdrawalDaoImpl {
+    pub pool: Pool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub txid: String,
+    pub receiver: Address,
+    pub amount: MicroAlgos,
+    pub round: u64,
+    pub round_time: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawalRecord {
+    pub txid: String,
+    /// None if not indexed (yet)
+    pub withdrawal: Option<Withdrawal>,
+    pub description: Option<String>,
+}
+
+#[async_trait]
+impl WithdrawalDao for WithdrawalDaoImpl {
+    async fn last_indexed_round(&self, project_uuid: &Uuid) -> Result<Option<u64>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        load_last_round(&client, project_uuid, TABLE.name).await
+    }
+
+    async fn save_withdrawals(
+        &self,
+        project_uuid: &Uuid,
+        withdrawals: &[Withdrawal],
+        round: u64,
+    ) -> Result<(), ServiceError> {
+        save_indexed_txs(
+            &self.pool,
+            &TABLE,
+            project_uuid,
+            withdrawals,
+            round,
+            |withdrawal| {
+                Ok(vec![
+                    row_param(withdrawal.txid.clone()),
+                    row_param(withdrawal.receiver.to_string()),
+                    row_param(to_numeric(withdrawal.amount.0)),
+                    row_param(to_bigint(withdrawal.round)?),
+                    row_param(withdrawal.round_time),
+                ])
+            },
+        )
+        .await
+    }
+
+    async fn save_description(
+        &self,
+        project_uuid: &Uuid,
+        txid: &str,
+        description: &str,
+    ) -> Result<(), ServiceError> {
+        let client = get_client(&self.pool).await?;
+        client
+            .execute(
+                "INSERT INTO withdrawal_descriptions (txid, project_id, description)
+                SELECT $2, id, $3 FROM project WHERE uuid=$1::TEXT::UUID
+                ON CONFLICT (project_id, txid) DO UPDATE SET description=excluded.description, updated_at=now();",
+                &[&project_uuid.to_string(), &txid, &description],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_withdrawals(
+        &self,
+        project_uuid: &Uuid,
+    ) -> Result<Vec<WithdrawalRecord>, ServiceError> {
+        let client = get_client(&self.pool).await?;
+        let rows = client
+            .query(
+                "WITH p AS (SELECT id FROM project WHERE uuid=$1::TEXT::UUID),
+                w AS (SELECT * FROM withdrawals WHERE project_id=(SELECT id FROM p)),
+                d AS (SELECT * FROM withdrawal_descriptions WHERE project_id=(SELECT id FROM p))
+                SELECT COALESCE(w.txid, d.txid), w.receiver, w.amount, w.round, w.round_time, d.description
+                FROM w FULL OUTER JOIN d ON d.txid = w.txid
+                ORDER BY w.round DESC NULLS FIRST, d.created_at DESC;",
+                &[&project_uuid.to_string()],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(withdrawal_record_from_row)
+            .collect::<anyhow::Result<Vec<_>>>()?)
+    }
+}
+
+fn withdrawal_record_from_row(row: &Row) -> anyhow::Result<WithdrawalRecord> {
+    let txid: String = row.get(0);
+    // the withdrawal columns are null if it's not indexed
+    let withdrawal = match row.get::<_, Option<String>>(1) {
+        Some(_) => Some(Withdrawal {
+            txid: txid.clone(),
+            receiver: get_address(row, 1)?,
+            amount: get_microalgos(row, 2)?,
+            round: get_id(row, 3)?,
+            round_time: row.get(4),
+        }),
+        None => None,
+    };
+    Ok(WithdrawalRecord {
+        txid,
+        withdrawal,
+        description: row.get(5),
+    })
+}
@@ -0,0 +1,109 @@
+use algonaut::core::Address;
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ServiceError, validation::validate_withdrawal_description};
+
+use super::{
+    project_dao::ProjectDao,
+    project_service::{load_own_project, parse_uuid},
+    withdrawal_dao::{WithdrawalDao, WithdrawalRecord},
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WithdrawalDescriptionJson {
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WithdrawalsJson {
+    pub withdrawals: Vec<WithdrawalJson>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalStatus {
+    /// Described, but not indexed (yet)
+    Pending,
+    Confirmed,
+}
+
+/// The on-chain fields are set if confirmed
+#[derive(Debug, Serialize)]
+pub struct WithdrawalJson {
+    pub txid: String,
+    pub status: WithdrawalStatus,
+    pub receiver: Option<String>,
+    /// Microalgos
+    pub amount: Option<u64>,
+    pub round: Option<u64>,
+    pub round_time: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+}
+
+impl From<WithdrawalRecord> for WithdrawalJson {
+    fn from(record: WithdrawalRecord) -> Self {
+        let status = match record.withdrawal {
+            Some(_) => WithdrawalStatus::Confirmed,
+            None => WithdrawalStatus::Pending,
+        };
+        WithdrawalJson {
+            txid: record.txid,
+            status,
+            receiver: record.withdrawal.as_ref().map(|w| w.receiver.to_string()),
+            amount: record.withdrawal.as_ref().map(|w| w.amount.0),
+            round: record.withdrawal.as_ref().map(|w| w.round),
+            round_time: record.withdrawal.as_ref().map(|w| w.round_time),
+            description: record.description,
+        }
+    }
+}
+
+/// Pending first, then newest first
+pub async fn load_withdrawals(
+    project_dao: &dyn ProjectDao,
+    withdrawal_dao: &dyn WithdrawalDao,
+    uuid: &str,
+) -> Result<WithdrawalsJson, ServiceError> {
+    let uuid = parse_uuid(uuid)?;
+    // not found error for unknown projects
+    project_dao.load_project(&uuid).await?;
+
+    let records = withdrawal_dao.load_withdrawals(&uuid).await?;
+    Ok(WithdrawalsJson {
+        withdrawals: records.into_iter().map(|r| r.into()).collect(),
+    })
+}
+
+/// The withdrawal can be described before it's submitted or indexed, so the txid isn't checked against the chain.
+/// Descriptions are per project: describing a txid doesn't affect other projects.
+pub async fn save_withdrawal_description(
+    project_dao: &dyn ProjectDao,
+    withdrawal_dao: &dyn WithdrawalDao,
+    caller: &Address,
+    uuid: &str,
+    txid: &str,
+    json: &WithdrawalDescriptionJson,
+) -> Result<(), ServiceError> {
+    let description = validate_withdrawal_description(&json.description)?;
+    let txid = parse_txid(txid)?;
+    let uuid = parse_uuid(uuid)?;
+    load_own_project(project_dao, caller, &uuid).await?;
+
+    withdrawal_dao
+        .save_description(&uuid, &txid, &description)
+        .await
+}
+
+/// Txids are the base32 (without padding) of a 32 bytes hash
+pub fn parse_txid(txid: &str) -> Result<String, ServiceError> {
+    match BASE32_NOPAD.decode(txid.as_bytes()) {
+        Ok(bytes) if bytes.len() == 32 => Ok(txid.to_owned()),
+        _ => Err(ServiceError::InvalidInput(format!(
+            "Invalid transaction id: {}",
+            txid
+        ))),
+    }
+}
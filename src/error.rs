@@ -0,0 +1,161 @@
+use std::convert::Infallible;
+
+use deadpool_postgres::PoolError;
+use serde::Serialize;
+use thiserror::Error;
+use tokio_postgres::error::SqlState;
+use warp::{
+    body::BodyDeserializeError,
+    http::StatusCode,
+    reject::{InvalidQuery, MethodNotAllowed, PayloadTooLarge, Reject},
+    Rejection, Reply,
+};
+
+use crate::validation::FieldError;
+
+#[derive(Debug, Error)]
+pub enum ServiceError {
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Validation failed: {0:?}")]
+    Validation(Vec<FieldError>),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    /// A service we depend on (database, chain) is unavailable or failed
+    #[error("Upstream error: {0}")]
+    Upstream(String),
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ServiceError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ServiceError::NotFound(_) => StatusCode::NOT_FOUND,
+            ServiceError::InvalidInput(_) | ServiceError::Validation(_) => StatusCode::BAD_REQUEST,
+            ServiceError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ServiceError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ServiceError::Conflict(_) => StatusCode::CONFLICT,
+            ServiceError::Upstream(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ServiceError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Machine readable, part of the API: don't change existing codes.
+    fn code(&self) -> &'static str {
+        match self {
+            ServiceError::NotFound(_) => "not_found",
+            ServiceError::InvalidInput(_) => "invalid_input",
+            ServiceError::Validation(_) => "validation_failed",
+            ServiceError::Unauthorized(_) => "unauthorized",
+            ServiceError::Forbidden(_) => "forbidden",
+            ServiceError::Conflict(_) => "conflict",
+            ServiceError::Upstream(_) => "upstream_unavailable",
+            ServiceError::Internal(_) => "internal",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            // don't leak internals to clients
+            ServiceError::Upstream(_) => "A dependency is currently unavailable".to_owned(),
+            ServiceError::Internal(_) => "Internal error".to_owned(),
+            ServiceError::Validation(_) => "Validation failed".to_owned(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl Reject for ServiceError {}
+
+impl From<tokio_postgres::Error> for ServiceError {
+    fn from(e: tokio_postgres::Error) -> Self {
+        match e.code() {
+            Some(code) if *code == SqlState::UNIQUE_VIOLATION => {
+                ServiceError::Conflict(e.to_string())
+            }
+            Some(_) => ServiceError::Internal(e.to_string()),
+            // no sql state: connection level error
+            None => ServiceError::Upstream(e.to_string()),
+        }
+    }
+}
+
+impl From<PoolError> for ServiceError {
+    fn from(e: PoolError) -> Self {
+        ServiceError::Upstream(e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for ServiceError {
+    fn from(e: anyhow::Error) -> Self {
+        ServiceError::Internal(e.to_string())
+    }
+}
+
+/// Error body returned by all the endpoints
+#[derive(Debug, Serialize)]
+pub struct ErrorJson {
+    pub code: String,
+    pub message: String,
+    /// Only for validation errors
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
+pub async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Infallible> {
+    let mut fields = vec![];
+    let (status, code, message) = if let Some(e) = rejection.find::<ServiceError>() {
+        if e.status().is_server_error() {
+            log::error!("Request failed: {}", e);
+        }
+        if let ServiceError::Validation(errors) = e {
+            fields = errors.clone();
+        }
+        (e.status(), e.code(), e.message())
+    } else if rejection.is_not_found() {
+        (
+            StatusCode::NOT_FOUND,
+            "not_found",
+            "Route not found".to_owned(),
+        )
+    } else if let Some(e) = rejection.find::<BodyDeserializeError>() {
+        (StatusCode::BAD_REQUEST, "invalid_input", e.to_string())
+    } else if let Some(e) = rejection.find::<InvalidQuery>() {
+        (StatusCode::BAD_REQUEST, "invalid_input", e.to_string())
+    } else if rejection.find::<PayloadTooLarge>().is_some() {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            "Payload too large".to_owned(),
+        )
+    } else if rejection.find::<MethodNotAllowed>().is_some() {
+        (
+            StatusCode::METHOD_NOT_ALLOWED,
+            "method_not_allowed",
+            "Method not allowed".to_owned(),
+        )
+    } else {
+        log::error!("Unhandled rejection: {:?}", rejection);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            "Internal error".to_owned(),
+        )
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ErrorJson {
+            code: code.to_owned(),
+            message,
+            fields,
+        }),
+        status,
+    ))
+}
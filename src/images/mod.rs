@@ -0,0 +1,130 @@
+use std::convert::TryFrom;
+
+use algonaut::core::Address;
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    dao::image_dao::{Image, ImageDao},
+    error::ServiceError,
+    storage::BlobStore,
+};
+
+use self::processing::process_image;
+
+pub mod processing;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImagesConfig {
+    pub max_size_bytes: u64,
+    /// Max width and height (pixels)
+    pub max_dimension: u32,
+    /// Max width and height of the thumbnails (pixels)
+    pub thumbnail_size: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageJson {
+    pub id: String,
+    pub content_type: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl From<Image> for ImageJson {
+    fn from(image: Image) -> Self {
+        ImageJson {
+            id: image.id.to_string(),
+            content_type: image.content_type,
+            width: image.width,
+            height: image.height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageVariant {
+    Original,
+    Thumbnail,
+}
+
+/// Validates and processes (see processing::process_image) the image, and stores it with its thumbnail.
+pub async fn upload_image(
+    dao: &dyn ImageDao,
+    store: &dyn BlobStore,
+    config: &ImagesConfig,
+    uploader: &Address,
+    bytes: Vec<u8>,
+) -> Result<Image, ServiceError> {
+    let config = config.clone();
+    let processed = tokio::task::spawn_blocking(move || process_image(&bytes, &config))
+        .await
+        .map_err(|e| ServiceError::Internal(format!("Image processing task failed: {}", e)))??;
+
+    let image = Image {
+        id: Uuid::new_v4(),
+        uploader: *uploader,
+        content_type: processed.content_type.to_owned(),
+        width: processed.width,
+        height: processed.height,
+        size_bytes: u32::try_from(processed.bytes.len()).map_err(Error::from)?,
+    };
+
+    let original_key = blob_key(&image.id, ImageVariant::Original);
+    let thumbnail_key = blob_key(&image.id, ImageVariant::Thumbnail);
+    // content first: an image row always has content
+    let res = async {
+        store
+            .put(&original_key, &image.content_type, processed.bytes)
+            .await?;
+        store
+            .put(&thumbnail_key, &image.content_type, processed.thumbnail)
+            .await?;
+        dao.save_image(&image).await
+    }
+    .await;
+    if let Err(e) = res {
+        // nothing references the content without the row
+        for key in &[original_key, thumbnail_key] {
+            if let Err(delete_error) = store.delete(key).await {
+                log::error!(
+                    "Couldn't delete the content of unsaved image: {}: {}",
+                    key,
+                    delete_error
+                );
+            }
+        }
+        return Err(e);
+    }
+
+    log::debug!("Saved image: {:?}", image);
+    Ok(image)
+}
+
+/// Returns the content type and the content
+pub async fn load_image_content(
+    dao: &dyn ImageDao,
+    store: &dyn BlobStore,
+    id: &str,
+    variant: ImageVariant,
+) -> Result<(String, Vec<u8>), ServiceError> {
+    let image = dao.load_image(&parse_image_id(id)?).await?;
+    let bytes = store
+        .get(&blob_key(&image.id, variant))
+        .await?
+        .ok_or_else(|| ServiceError::Internal(format!("Image content missing: {}", image.id)))?;
+    Ok((image.content_type, bytes))
+}
+
+pub fn parse_image_id(id: &str) -> Result<Uuid, ServiceError> {
+    id.parse()
+        .map_err(|_| ServiceError::InvalidInput(format!("Invalid image id: {}", id)))
+}
+
+fn blob_key(id: &Uuid, variant: ImageVariant) -> String {
+    match variant {
+        ImageVariant::Original => format!("images/{}", id),
+        ImageVariant::Thumbnail => format!("thumbnails/{}", id),
+    }
+}
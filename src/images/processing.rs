@@ -0,0 +1,171 @@
+use std::io::Cursor;
+
+use exif::{In, Tag};
+use image::{io::Reader, DynamicImage, ImageFormat, ImageOutputFormat};
+
+use crate::error::ServiceError;
+
+use super::ImagesConfig;
+
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug)]
+pub struct ProcessedImage {
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+    /// Fits in a square of `ImagesConfig::thumbnail_size`, same content type
+    pub thumbnail: Vec<u8>,
+}
+
+/// Validates an uploaded image and re-encodes it, which drops all the metadata (EXIF, including location).
+/// The EXIF orientation is applied to the pixels first, so the image isn't displayed rotated without it.
+/// JPEGs stay JPEGs, the other formats are converted to PNG.
+/// CPU intensive: call it in a blocking task.
+pub fn process_image(bytes: &[u8], config: &ImagesConfig) -> Result<ProcessedImage, ServiceError> {
+    if bytes.len() as u64 > config.max_size_bytes {
+        return Err(ServiceError::InvalidInput(format!(
+            "Image too large, max size: {} bytes",
+            config.max_size_bytes
+        )));
+    }
+
+    let (output_format, content_type) = match reader(bytes)?.format() {
+        Some(ImageFormat::Jpeg) => (ImageOutputFormat::Jpeg(JPEG_QUALITY), "image/jpeg"),
+        Some(ImageFormat::Png) | Some(ImageFormat::Gif) | Some(ImageFormat::WebP) => {
+            (ImageOutputFormat::Png, "image/png")
+        }
+        _ => {
+            return Err(ServiceError::InvalidInput(
+                "Unsupported image type, expected JPEG, PNG, GIF or WebP".to_owned(),
+            ))
+        }
+    };
+
+    // checked before decoding, as small files can decode to huge images
+    let (width, height) = reader(bytes)?
+        .into_dimensions()
+        .map_err(|e| ServiceError::InvalidInput(format!("Invalid image: {}", e)))?;
+    if width > config.max_dimension || height > config.max_dimension {
+        return Err(ServiceError::InvalidInput(format!(
+            "Image too large, max width and height: {}",
+            config.max_dimension
+        )));
+    }
+
+    let image = reader(bytes)?
+        .decode()
+        .map_err(|e| ServiceError::InvalidInput(format!("Invalid image: {}", e)))?;
+    let image = apply_orientation(image, exif_orientation(bytes));
+    let thumbnail = image.thumbnail(config.thumbnail_size, config.thumbnail_size);
+
+    Ok(ProcessedImage {
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        bytes: encode(&image, output_format.clone())?,
+        thumbnail: encode(&thumbnail, output_format)?,
+    })
+}
+
+fn reader(bytes: &[u8]) -> Result<Reader<Cursor<&[u8]>>, ServiceError> {
+    Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| ServiceError::Internal(e.to_string()))
+}
+
+/// The EXIF orientation (1 to 8), 1 (as stored) if the image has none or it can't be read
+fn exif_orientation(bytes: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .unwrap_or(1)
+}
+
+/// Transforms the image as the EXIF orientation says it's displayed
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+fn encode(image: &DynamicImage, format: ImageOutputFormat) -> Result<Vec<u8>, ServiceError> {
+    let mut bytes = vec![];
+    image
+        .write_to(&mut bytes, format)
+        .map_err(|e| ServiceError::Internal(format!("Couldn't encode image: {}", e)))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::process_image;
+    use crate::{error::ServiceError, images::ImagesConfig};
+    use anyhow::Result;
+    use image::{DynamicImage, ImageBuffer, ImageOutputFormat, Rgb};
+    use tokio::test;
+
+    #[test]
+    async fn test_process_image_creates_thumbnail() -> Result<()> {
+        let image = DynamicImage::ImageRgb8(ImageBuffer::from_fn(600, 300, |x, _| {
+            Rgb([(x % 256) as u8, 0, 0])
+        }));
+        let mut bytes = vec![];
+        image.write_to(&mut bytes, ImageOutputFormat::Png)?;
+
+        let processed = process_image(&bytes, &test_images_config())?;
+
+        assert_eq!("image/png", processed.content_type);
+        assert_eq!((600, 300), (processed.width, processed.height));
+        let thumbnail = image::load_from_memory(&processed.thumbnail)?;
+        assert_eq!((256, 128), (thumbnail.width(), thumbnail.height()));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_process_image_applies_exif_orientation() -> Result<()> {
+        let image = DynamicImage::ImageRgb8(ImageBuffer::from_pixel(600, 300, Rgb([0, 0, 0])));
+        let mut jpeg = vec![];
+        image.write_to(&mut jpeg, ImageOutputFormat::Jpeg(85))?;
+        // APP1 segment after SOI: big endian TIFF with one entry, orientation (0x0112) = 6 (rotated 90° cw)
+        let app1: &[u8] = &[
+            0xFF, 0xE1, 0x00, 0x22, b'E', b'x', b'i', b'f', 0, 0, b'M', b'M', 0x00, 0x2A, 0x00,
+            0x00, 0x00, 0x08, 0x00, 0x01, 0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00,
+            0x06, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let bytes = [&jpeg[..2], app1, &jpeg[2..]].concat();
+
+        let processed = process_image(&bytes, &test_images_config())?;
+
+        assert_eq!("image/jpeg", processed.content_type);
+        assert_eq!((300, 600), (processed.width, processed.height));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_process_image_rejects_other_content() -> Result<()> {
+        let res = process_image(b"<svg></svg>", &test_images_config());
+        assert!(matches!(res, Err(ServiceError::InvalidInput(_))));
+        Ok(())
+    }
+
+    fn test_images_config() -> ImagesConfig {
+        ImagesConfig {
+            max_size_bytes: 1_000_000,
+            max_dimension: 1000,
+            thumbnail_size: 256,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use algonaut::core::MicroAlgos;
+use anyhow::Error;
+use core_::flows::create_project::model::Project;
+use data_encoding::BASE64;
+
+use crate::{
+    chain::indexer::{AddressRole, ChainIndexer, IndexerTransaction, TransactionQuery, TxType},
+    dao::customer_payment_dao::{CustomerPayment, CustomerPaymentDao},
+    error::ServiceError,
+};
+
+use super::next_min_round;
+
+/// Indexes the payments to the project's customer escrow from the last indexed round up to `round`.
+pub async fn index_customer_payments(
+    indexer: &dyn ChainIndexer,
+    dao: &dyn CustomerPaymentDao,
+    project: &Project,
+    round: u64,
+) -> Result<(), ServiceError> {
+    let min_round = match next_min_round(dao.last_indexed_round(&project.uuid).await?, round) {
+        Some(min_round) => min_round,
+        None => return Ok(()),
+    };
+
+    let transactions = indexer
+        .transactions(&TransactionQuery {
+            address: project.customer_escrow.address().to_string(),
+            address_role: AddressRole::Receiver,
+            tx_type: TxType::Payment,
+            asset_id: None,
+            min_round,
+            max_round: round,
+        })
+        .await?;
+
+    let payments = transactions
+        .iter()
+        .filter_map(|tx| to_customer_payment(tx).transpose())
+        .collect::<Result<Vec<_>, _>>()?;
+    dao.save_customer_payments(&project.uuid, &payments, round)
+        .await
+}
+
+/// None if the transaction doesn't transfer algos
+fn to_customer_payment(tx: &IndexerTransaction) -> Result<Option<CustomerPayment>, ServiceError> {
+    let amount = match &tx.payment_transaction {
+        Some(payment) if payment.amount > 0 => payment.amount,
+        _ => return Ok(None),
+    };
+    let note = tx
+        .note
+        .as_ref()
+        .map(|note| BASE64.decode(note.as_bytes()))
+        .transpose()
+        .map_err(Error::from)?;
+
+    Ok(Some(CustomerPayment {
+        txid: tx.id.clone(),
+        payer: tx.sender.parse().map_err(Error::msg)?,
+        amount: MicroAlgos(amount),
+        note,
+        round: tx.confirmed_round,
+        round_time: tx.confirmed_at()?,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::to_customer_payment;
+    use crate::{
+        chain::indexer::IndexerTransaction,
+        test_data::{project_json, IndexerTxBuilder, CUSTOMER},
+    };
+    use algonaut::core::MicroAlgos;
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_customer_payment_from_transaction() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let escrow = project.customer_escrow.address().to_string();
+
+        let payment = to_customer_payment(&payment_tx(&escrow, 2_000_000, Some("b3JkZXIgNDI=")))?
+            .expect("Should be a payment");
+        assert_eq!(CUSTOMER, payment.payer.to_string());
+        assert_eq!(MicroAlgos(2_000_000), payment.amount);
+        assert_eq!(Some(b"order 42".to_vec()), payment.note);
+
+        // no algos transferred
+        assert_eq!(None, to_customer_payment(&payment_tx(&escrow, 0, None))?);
+
+        Ok(())
+    }
+
+    fn payment_tx(escrow: &str, amount: u64, note: Option<&str>) -> IndexerTransaction {
+        IndexerTxBuilder::new("TX1", CUSTOMER)
+            .note(note)
+            .payment(escrow, amount)
+            .build()
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use algonaut::core::MicroAlgos;
+use anyhow::Error;
+use core_::flows::create_project::model::Project;
+
+use crate::{
+    chain::indexer::{AddressRole, ChainIndexer, IndexerTransaction, TransactionQuery, TxType},
+    dao::investment_dao::{Investment, InvestmentDao},
+    error::ServiceError,
+};
+
+use super::next_min_round;
+
+/// Indexes the investments in the project from the last indexed round up to `round`.
+///
+/// An investment is a group where the investor pays the central escrow and the invest escrow
+/// transfers the shares (to the staking escrow, where they're locked for the investor).
+pub async fn index_investments(
+    indexer: &dyn ChainIndexer,
+    dao: &dyn InvestmentDao,
+    project: &Project,
+    round: u64,
+) -> Result<(), ServiceError> {
+    let min_round = match next_min_round(dao.last_indexed_round(&project.uuid).await?, round) {
+        Some(min_round) => min_round,
+        None => return Ok(()),
+    };
+
+    let share_transfers = indexer
+        .transactions(&TransactionQuery {
+            address: project.invest_escrow.address().to_string(),
+            address_role: AddressRole::Sender,
+            tx_type: TxType::AssetTransfer,
+            asset_id: Some(project.shares_asset_id),
+            min_round,
+            max_round: round,
+        })
+        .await?;
+    let payments = if share_transfers.is_empty() {
+        vec![]
+    } else {
+        indexer
+            .transactions(&TransactionQuery {
+                address: project.central_escrow.address().to_string(),
+                address_role: AddressRole::Receiver,
+                tx_type: TxType::Payment,
+                asset_id: None,
+                min_round,
+                max_round: round,
+            })
+            .await?
+    };
+
+    let investments = to_investments(&share_transfers, &payments)?;
+    dao.save_investments(&project.uuid, &investments, round)
+        .await
+}
+
+/// Matches the shares transfers with the payments in the same group
+fn to_investments(
+    share_transfers: &[IndexerTransaction],
+    payments: &[IndexerTransaction],
+) -> Result<Vec<Investment>, ServiceError> {
+    let payments_by_group: HashMap<&str, &IndexerTransaction> = payments
+        .iter()
+        .filter_map(|tx| tx.group.as_deref().map(|group| (group, tx)))
+        .collect();
+
+    let mut investments = vec![];
+    for transfer in share_transfers {
+        let (shares, payment) = match (
+            &transfer.asset_transfer_transaction,
+            transfer
+                .group
+                .as_deref()
+                .and_then(|group| payments_by_group.get(group)),
+        ) {
+            (Some(asset_transfer), Some(payment)) if asset_transfer.amount > 0 => {
+                (asset_transfer.amount, payment)
+            }
+            _ => {
+                log::debug!("Shares transfer isn't an investment: {}", transfer.id);
+                continue;
+            }
+        };
+        let paid = payment
+            .payment_transaction
+            .as_ref()
+            .map(|p| p.amount)
+            .unwrap_or_default();
+
+        investments.push(Investment {
+            txid: transfer.id.clone(),
+            investor: payment.sender.parse().map_err(Error::msg)?,
+            shares,
+            paid: MicroAlgos(paid),
+            round: transfer.confirmed_round,
+            round_time: transfer.confirmed_at()?,
+        });
+    }
+    Ok(investments)
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::to_investments;
+    use crate::{
+        chain::indexer::IndexerTransaction,
+        test_data::{project_json, IndexerTxBuilder, INVESTOR},
+    };
+    use algonaut::core::MicroAlgos;
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_matches_shares_transfers_with_payments() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+
+        let share_transfers = vec![
+            share_transfer(&project, "TX1", Some("GROUP1"), 10),
+            // no payment in the group
+            share_transfer(&project, "TX2", Some("GROUP2"), 5),
+            share_transfer(&project, "TX3", None, 5),
+        ];
+        let payments = vec![IndexerTxBuilder::new("TX4", INVESTOR)
+            .group(Some("GROUP1"))
+            .payment(&project.central_escrow.address().to_string(), 10_000_000)
+            .build()];
+
+        let investments = to_investments(&share_transfers, &payments)?;
+
+        assert_eq!(1, investments.len());
+        let investment = &investments[0];
+        assert_eq!("TX1", investment.txid);
+        assert_eq!(INVESTOR, investment.investor.to_string());
+        assert_eq!(10, investment.shares);
+        assert_eq!(MicroAlgos(10_000_000), investment.paid);
+        assert_eq!(100, investment.round);
+
+        Ok(())
+    }
+
+    fn share_transfer(
+        project: &Project,
+        id: &str,
+        group: Option<&str>,
+        shares: u64,
+    ) -> IndexerTransaction {
+        IndexerTxBuilder::new(id, &project.invest_escrow.address().to_string())
+            .group(group)
+            .asset_transfer(&project.staking_escrow.address().to_string(), shares)
+            .build()
+    }
+}
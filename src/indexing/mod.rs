@@ -0,0 +1,168 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use core_::flows::create_project::model::Project;
+use serde::Deserialize;
+use tokio::task::JoinHandle;
+
+use crate::{
+    chain::indexer::ChainIndexer,
+    dao::{
+        customer_payment_dao::CustomerPaymentDao, investment_dao::InvestmentDao,
+        project_dao::ProjectDao, project_stats_dao::ProjectStatsDao,
+        share_holder_dao::ShareHolderDao, staking_dao::StakingDao, withdrawal_dao::WithdrawalDao,
+    },
+    error::ServiceError,
+};
+
+pub mod customer_payments;
+pub mod investments;
+pub mod share_holders;
+pub mod staking;
+pub mod withdrawals;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct IndexingConfig {
+    /// Whether the background indexing task runs
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+}
+
+/// The daos where the indexed transactions are saved
+pub struct IndexingDaos {
+    pub project_dao: Arc<dyn ProjectDao>,
+    pub investment_dao: Arc<dyn InvestmentDao>,
+    pub customer_payment_dao: Arc<dyn CustomerPaymentDao>,
+    pub withdrawal_dao: Arc<dyn WithdrawalDao>,
+    pub staking_dao: Arc<dyn StakingDao>,
+    pub share_holder_dao: Arc<dyn ShareHolderDao>,
+    pub project_stats_dao: Arc<dyn ProjectStatsDao>,
+}
+
+/// Starts the task that periodically indexes the transactions of all the projects, up to the indexer's current round.
+/// Each project resumes where it was left (see dao::indexer_cursor).
+pub fn start_indexing(
+    config: &IndexingConfig,
+    indexer: Arc<dyn ChainIndexer>,
+    daos: IndexingDaos,
+) -> JoinHandle<()> {
+    let poll_interval = Duration::from_secs(config.poll_interval_secs);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = index_projects(&*indexer, &daos).await {
+                log::error!("Indexing failed: {}", e);
+            }
+        }
+    })
+}
+
+async fn index_projects(
+    indexer: &dyn ChainIndexer,
+    daos: &IndexingDaos,
+) -> Result<(), ServiceError> {
+    let round = indexer.current_round().await?;
+    for stored in daos.project_dao.load_all_projects().await? {
+        let project = &stored.project;
+        // a failing project doesn't block the others. It's retried in the next poll.
+        log_if_failed(
+            "investments",
+            project,
+            investments::index_investments(indexer, &*daos.investment_dao, project, round).await,
+        );
+        // refreshed each poll (not only with new investments): the last 7 days window moves
+        log_if_failed(
+            "stats",
+            project,
+            daos.project_stats_dao.refresh_stats(&project.uuid).await,
+        );
+        log_if_failed(
+            "customer payments",
+            project,
+            customer_payments::index_customer_payments(
+                indexer,
+                &*daos.customer_payment_dao,
+                project,
+                round,
+            )
+            .await,
+        );
+        log_if_failed(
+            "withdrawals",
+            project,
+            withdrawals::index_withdrawals(indexer, &*daos.withdrawal_dao, project, round).await,
+        );
+        log_if_failed(
+            "staking events",
+            project,
+            staking::index_staking_events(indexer, &*daos.staking_dao, project, round).await,
+        );
+        // after the investments and staking events: the staked shares are computed from them
+        log_if_failed(
+            "share holders",
+            project,
+            refresh_share_holders(indexer, daos, project, round).await,
+        );
+    }
+    Ok(())
+}
+
+/// Only once the investments and staking events are indexed up to `round`:
+/// otherwise the staked shares would be stale, but saved as of `round`. Retried in the next poll.
+async fn refresh_share_holders(
+    indexer: &dyn ChainIndexer,
+    daos: &IndexingDaos,
+    project: &Project,
+    round: u64,
+) -> Result<(), ServiceError> {
+    let investments_round = daos
+        .investment_dao
+        .last_indexed_round(&project.uuid)
+        .await?;
+    let staking_round = daos.staking_dao.last_indexed_round(&project.uuid).await?;
+    if investments_round < Some(round) || staking_round < Some(round) {
+        log::debug!(
+            "Share holders not refreshed for project: {}: investments or staking events not indexed up to round: {}",
+            project.uuid,
+            round
+        );
+        return Ok(());
+    }
+    share_holders::refresh_share_holders(indexer, &*daos.share_holder_dao, project, round).await
+}
+
+fn log_if_failed(kind: &str, project: &Project, res: Result<(), ServiceError>) {
+    if let Err(e) = res {
+        log::error!(
+            "Indexing {} failed for project: {}: {}",
+            kind,
+            project.uuid,
+            e
+        );
+    }
+}
+
+/// The groups (base64 ids) calling the central app in the rounds (inclusive).
+/// The central escrow pays only in such groups (harvests), except for the creator's withdrawals.
+async fn central_app_call_groups(
+    indexer: &dyn ChainIndexer,
+    project: &Project,
+    min_round: u64,
+    max_round: u64,
+) -> Result<HashSet<String>, ServiceError> {
+    Ok(indexer
+        .app_calls(project.central_app_id, min_round, max_round)
+        .await?
+        .into_iter()
+        .filter_map(|tx| tx.group)
+        .collect())
+}
+
+/// The first round to index, given the last indexed one. None if already indexed up to `round`.
+fn next_min_round(last_indexed_round: Option<u64>, round: u64) -> Option<u64> {
+    match last_indexed_round {
+        Some(last_round) if last_round >= round => None,
+        Some(last_round) => Some(last_round + 1),
+        None => Some(0),
+    }
+}
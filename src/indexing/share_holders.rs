@@ -0,0 +1,79 @@
+use algonaut::core::Address;
+use anyhow::Error;
+use core_::flows::create_project::model::Project;
+
+use crate::{
+    chain::indexer::{AssetBalance, ChainIndexer},
+    dao::share_holder_dao::ShareHolderDao,
+    error::ServiceError,
+};
+
+use super::next_min_round;
+
+/// Refreshes the project's share holder registry, if the chain advanced since the last refresh.
+/// To be run after the staking events were indexed up to `round`, as the staked shares are taken from them.
+pub async fn refresh_share_holders(
+    indexer: &dyn ChainIndexer,
+    dao: &dyn ShareHolderDao,
+    project: &Project,
+    round: u64,
+) -> Result<(), ServiceError> {
+    if next_min_round(dao.last_indexed_round(&project.uuid).await?, round).is_none() {
+        return Ok(());
+    }
+    let balances = indexer.asset_balances(project.shares_asset_id).await?;
+    let free_shares = to_free_shares(&balances, project)?;
+    dao.save_share_holders(&project.uuid, &free_shares, round)
+        .await
+}
+
+/// The escrows' shares aren't owned by them: the invest escrow holds the shares not sold yet,
+/// the staking escrow the staked shares, which are attributed to the investors.
+fn to_free_shares(
+    balances: &[AssetBalance],
+    project: &Project,
+) -> Result<Vec<(Address, u64)>, ServiceError> {
+    let escrows = [
+        project.invest_escrow.address().to_string(),
+        project.staking_escrow.address().to_string(),
+    ];
+    Ok(balances
+        .iter()
+        .filter(|balance| balance.amount > 0 && !escrows.contains(&balance.address))
+        .map(|balance| Ok((balance.address.parse().map_err(Error::msg)?, balance.amount)))
+        .collect::<Result<Vec<_>, Error>>()?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::to_free_shares;
+    use crate::{chain::indexer::AssetBalance, test_data::project_json};
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_escrows_are_not_holders() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let investor = project.creator.to_string();
+        let balances = vec![
+            balance(&project.invest_escrow.address().to_string(), 60),
+            balance(&project.staking_escrow.address().to_string(), 30),
+            balance(&investor, 10),
+        ];
+
+        let free_shares = to_free_shares(&balances, &project)?;
+
+        assert_eq!(vec![(project.creator, 10)], free_shares);
+        Ok(())
+    }
+
+    fn balance(address: &str, amount: u64) -> AssetBalance {
+        AssetBalance {
+            address: address.to_owned(),
+            amount,
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+
+use anyhow::Error;
+use core_::flows::create_project::model::Project;
+
+use crate::{
+    chain::indexer::{AddressRole, ChainIndexer, IndexerTransaction, TransactionQuery, TxType},
+    dao::staking_dao::{InvestorEventKind, StakingDao, StakingEvent},
+    error::ServiceError,
+};
+
+use super::{central_app_call_groups, next_min_round};
+
+/// Indexes the stakes, unstakes and harvests in the project from the last indexed round up to `round`.
+///
+/// Staking transfers shares from the investor to the staking escrow and unstaking back.
+/// Harvesting pays dividends from the central escrow to the investor, in a group calling the central app.
+pub async fn index_staking_events(
+    indexer: &dyn ChainIndexer,
+    dao: &dyn StakingDao,
+    project: &Project,
+    round: u64,
+) -> Result<(), ServiceError> {
+    let min_round = match next_min_round(dao.last_indexed_round(&project.uuid).await?, round) {
+        Some(min_round) => min_round,
+        None => return Ok(()),
+    };
+    let staking_escrow = project.staking_escrow.address().to_string();
+    let query = |address: &str, address_role: AddressRole, tx_type: TxType| TransactionQuery {
+        address: address.to_owned(),
+        address_role,
+        tx_type,
+        asset_id: match tx_type {
+            TxType::AssetTransfer => Some(project.shares_asset_id),
+            _ => None,
+        },
+        min_round,
+        max_round: round,
+    };
+
+    let share_transfers_in = indexer
+        .transactions(&query(
+            &staking_escrow,
+            AddressRole::Receiver,
+            TxType::AssetTransfer,
+        ))
+        .await?;
+    let share_transfers_out = indexer
+        .transactions(&query(
+            &staking_escrow,
+            AddressRole::Sender,
+            TxType::AssetTransfer,
+        ))
+        .await?;
+    let central_payments = indexer
+        .transactions(&query(
+            &project.central_escrow.address().to_string(),
+            AddressRole::Sender,
+            TxType::Payment,
+        ))
+        .await?;
+    let app_call_groups = central_app_call_groups(indexer, project, min_round, round).await?;
+
+    let events = to_staking_events(
+        &share_transfers_in,
+        &share_transfers_out,
+        &central_payments,
+        &app_call_groups,
+        project,
+    )?;
+    dao.save_staking_events(&project.uuid, &events, round).await
+}
+
+fn to_staking_events(
+    share_transfers_in: &[IndexerTransaction],
+    share_transfers_out: &[IndexerTransaction],
+    central_payments: &[IndexerTransaction],
+    app_call_groups: &HashSet<String>,
+    project: &Project,
+) -> Result<Vec<StakingEvent>, ServiceError> {
+    let invest_escrow = project.invest_escrow.address().to_string();
+
+    let mut events = vec![];
+    // investments stake the shares too, but they're indexed as investments
+    for tx in share_transfers_in
+        .iter()
+        .filter(|tx| tx.sender != invest_escrow)
+    {
+        if let Some(transfer) = &tx.asset_transfer_transaction {
+            events.push(to_staking_event(
+                tx,
+                InvestorEventKind::Stake,
+                &tx.sender,
+                transfer.amount,
+            )?);
+        }
+    }
+    for tx in share_transfers_out {
+        if let Some(transfer) = &tx.asset_transfer_transaction {
+            events.push(to_staking_event(
+                tx,
+                InvestorEventKind::Unstake,
+                &transfer.receiver,
+                transfer.amount,
+            )?);
+        }
+    }
+    // the other payments of the central escrow are withdrawals
+    for tx in central_payments.iter().filter(|tx| {
+        tx.group
+            .as_ref()
+            .map_or(false, |group| app_call_groups.contains(group))
+    }) {
+        if let Some(payment) = &tx.payment_transaction {
+            events.push(to_staking_event(
+                tx,
+                InvestorEventKind::Harvest,
+                &payment.receiver,
+                payment.amount,
+            )?);
+        }
+    }
+    Ok(events
+        .into_iter()
+        .filter(|event| event.amount > 0)
+        .collect())
+}
+
+fn to_staking_event(
+    tx: &IndexerTransaction,
+    kind: InvestorEventKind,
+    investor: &str,
+    amount: u64,
+) -> Result<StakingEvent, ServiceError> {
+    Ok(StakingEvent {
+        txid: tx.id.clone(),
+        investor: investor.parse().map_err(Error::msg)?,
+        kind,
+        amount,
+        round: tx.confirmed_round,
+        round_time: tx.confirmed_at()?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, convert::TryInto};
+
+    use super::to_staking_events;
+    use crate::{
+        chain::indexer::IndexerTransaction,
+        dao::staking_dao::InvestorEventKind,
+        test_data::{project_json, IndexerTxBuilder, INVESTOR},
+    };
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_stakes_and_unstakes() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let invest_escrow = project.invest_escrow.address().to_string();
+        let staking_escrow = project.staking_escrow.address().to_string();
+
+        let transfers_in = vec![
+            share_transfer_tx("TX1", INVESTOR, &staking_escrow, 10),
+            // investment: indexed as such
+            share_transfer_tx("TX2", &invest_escrow, &staking_escrow, 20),
+            // opt-in
+            share_transfer_tx("TX3", &staking_escrow, &staking_escrow, 0),
+        ];
+        let transfers_out = vec![share_transfer_tx("TX4", &staking_escrow, INVESTOR, 4)];
+
+        let events = to_staking_events(
+            &transfers_in,
+            &transfers_out,
+            &[],
+            &HashSet::new(),
+            &project,
+        )?;
+
+        let events: Vec<(&str, String, InvestorEventKind, u64)> = events
+            .iter()
+            .map(|e| (e.txid.as_str(), e.investor.to_string(), e.kind, e.amount))
+            .collect();
+        assert_eq!(
+            vec![
+                ("TX1", INVESTOR.to_owned(), InvestorEventKind::Stake, 10),
+                // the investor is the receiver
+                ("TX4", INVESTOR.to_owned(), InvestorEventKind::Unstake, 4),
+            ],
+            events
+        );
+        Ok(())
+    }
+
+    #[test]
+    async fn test_harvests_exclude_withdrawals() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let central_escrow = project.central_escrow.address().to_string();
+
+        let creator = project.creator.to_string();
+        let app_call_groups: HashSet<String> = vec!["HARVEST1".to_owned(), "HARVEST2".to_owned()]
+            .into_iter()
+            .collect();
+
+        let payments = vec![
+            payment_tx(
+                "TX1",
+                &central_escrow,
+                INVESTOR,
+                1_000_000,
+                Some("HARVEST1"),
+            ),
+            // withdrawal
+            payment_tx("TX2", &central_escrow, &creator, 5_000_000, None),
+            // the creator harvesting their own shares
+            payment_tx(
+                "TX3",
+                &central_escrow,
+                &creator,
+                2_000_000,
+                Some("HARVEST2"),
+            ),
+        ];
+
+        let events = to_staking_events(&[], &[], &payments, &app_call_groups, &project)?;
+
+        let events: Vec<(&str, String, InvestorEventKind, u64)> = events
+            .iter()
+            .map(|e| (e.txid.as_str(), e.investor.to_string(), e.kind, e.amount))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "TX1",
+                    INVESTOR.to_owned(),
+                    InvestorEventKind::Harvest,
+                    1_000_000
+                ),
+                ("TX3", creator, InvestorEventKind::Harvest, 2_000_000),
+            ],
+            events
+        );
+        Ok(())
+    }
+
+    fn share_transfer_tx(
+        id: &str,
+        sender: &str,
+        receiver: &str,
+        amount: u64,
+    ) -> IndexerTransaction {
+        IndexerTxBuilder::new(id, sender)
+            .asset_transfer(receiver, amount)
+            .build()
+    }
+
+    fn payment_tx(
+        id: &str,
+        sender: &str,
+        receiver: &str,
+        amount: u64,
+        group: Option<&str>,
+    ) -> IndexerTransaction {
+        IndexerTxBuilder::new(id, sender)
+            .group(group)
+            .payment(receiver, amount)
+            .build()
+    }
+}
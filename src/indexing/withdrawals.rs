@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use algonaut::core::MicroAlgos;
+use anyhow::Error;
+use core_::flows::create_project::model::Project;
+
+use crate::{
+    chain::indexer::{AddressRole, ChainIndexer, IndexerTransaction, TransactionQuery, TxType},
+    dao::withdrawal_dao::{Withdrawal, WithdrawalDao},
+    error::ServiceError,
+};
+
+use super::{central_app_call_groups, next_min_round};
+
+/// Indexes the withdrawals of the project from the last indexed round up to `round`.
+pub async fn index_withdrawals(
+    indexer: &dyn ChainIndexer,
+    dao: &dyn WithdrawalDao,
+    project: &Project,
+    round: u64,
+) -> Result<(), ServiceError> {
+    let min_round = match next_min_round(dao.last_indexed_round(&project.uuid).await?, round) {
+        Some(min_round) => min_round,
+        None => return Ok(()),
+    };
+
+    let transactions = indexer
+        .transactions(&TransactionQuery {
+            address: project.central_escrow.address().to_string(),
+            address_role: AddressRole::Sender,
+            tx_type: TxType::Payment,
+            asset_id: None,
+            min_round,
+            max_round: round,
+        })
+        .await?;
+    let app_call_groups = central_app_call_groups(indexer, project, min_round, round).await?;
+
+    let creator = project.creator.to_string();
+    let withdrawals = transactions
+        .iter()
+        .filter_map(|tx| to_withdrawal(tx, &creator, &app_call_groups).transpose())
+        .collect::<Result<Vec<_>, _>>()?;
+    dao.save_withdrawals(&project.uuid, &withdrawals, round)
+        .await
+}
+
+/// The central escrow also pays the investors (dividends), in groups calling the central app (harvests):
+/// withdrawals are the payments to the creator outside of these groups (the creator can harvest too).
+fn to_withdrawal(
+    tx: &IndexerTransaction,
+    creator: &str,
+    app_call_groups: &HashSet<String>,
+) -> Result<Option<Withdrawal>, ServiceError> {
+    let is_harvest = tx
+        .group
+        .as_ref()
+        .map_or(false, |group| app_call_groups.contains(group));
+    let payment = match &tx.payment_transaction {
+        Some(payment) if !is_harvest && payment.receiver == creator && payment.amount > 0 => {
+            payment
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(Withdrawal {
+        txid: tx.id.clone(),
+        receiver: payment.receiver.parse().map_err(Error::msg)?,
+        amount: MicroAlgos(payment.amount),
+        round: tx.confirmed_round,
+        round_time: tx.confirmed_at()?,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, convert::TryInto};
+
+    use super::to_withdrawal;
+    use crate::{
+        chain::indexer::IndexerTransaction,
+        test_data::{project_json, IndexerTxBuilder, CREATOR, INVESTOR},
+    };
+    use algonaut::core::MicroAlgos;
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_only_payments_to_the_creator_are_withdrawals() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+        let central_escrow = project.central_escrow.address().to_string();
+        let payment_tx = |receiver: &str, amount: u64, group: Option<&str>| -> IndexerTransaction {
+            IndexerTxBuilder::new("TX1", &central_escrow)
+                .group(group)
+                .payment(receiver, amount)
+                .build()
+        };
+        let app_call_groups: HashSet<String> = vec!["HARVEST".to_owned()].into_iter().collect();
+
+        let withdrawal = to_withdrawal(
+            &payment_tx(CREATOR, 5_000_000, None),
+            CREATOR,
+            &app_call_groups,
+        )?
+        .expect("Should be a withdrawal");
+        assert_eq!(CREATOR, withdrawal.receiver.to_string());
+        assert_eq!(MicroAlgos(5_000_000), withdrawal.amount);
+
+        // dividend
+        assert_eq!(
+            None,
+            to_withdrawal(
+                &payment_tx(INVESTOR, 1_000_000, Some("HARVEST")),
+                CREATOR,
+                &app_call_groups
+            )?
+        );
+        // the creator harvesting their own shares
+        assert_eq!(
+            None,
+            to_withdrawal(
+                &payment_tx(CREATOR, 1_000_000, Some("HARVEST")),
+                CREATOR,
+                &app_call_groups
+            )?
+        );
+
+        Ok(())
+    }
+}
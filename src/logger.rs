@@ -1,4 +1,4 @@
-pub fn init_logger() {
+pub fn init_logger(config_path: &str) {
     // println!("current dir: {:?}", std::env::current_dir());
-    log4rs::init_file("./log_config.yml", Default::default()).expect("Couldn't initialize logger")
+    log4rs::init_file(config_path, Default::default()).expect("Couldn't initialize logger")
 }
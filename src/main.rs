@@ -1,8 +1,6 @@
-use std::{
-    convert::{Infallible, TryInto},
-    sync::Arc,
-};
+use std::sync::Arc;
 
+use algonaut::{algod::v2::Algod, core::Address};
 use anyhow::Result;
 use core_::{
     api::{
@@ -12,30 +10,187 @@ use core_::{
     flows::create_project::model::Project,
 };
 use dao::project_dao::ProjectDao;
+use futures::TryStreamExt;
 use logger::init_logger;
-use warp::Filter;
+use serde::{Deserialize, Serialize};
+use warp::{
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        uri::InvalidUri,
+        Response, StatusCode, Uri,
+    },
+    hyper::body::Buf,
+    multipart::{FormData, Part},
+    Filter, Rejection,
+};
 
-use crate::dao::{db::create_db_client, project_dao::ProjectDaoImpl, project_service};
+use crate::auth::session::{self, ChallengeRequestJson, VerifyChallengeJson};
+use crate::chain::{
+    create_algod, create_indexer,
+    indexer::ChainIndexer,
+    project_verifier::{NoopProjectVerifier, ProjectVerifier, ProjectVerifierImpl},
+    tx_submitter::TxSubmitter,
+};
+use crate::config::{Command, Config, Opt};
+use crate::dao::{
+    auth_dao::{AuthDao, AuthDaoImpl},
+    customer_payment_dao::{CustomerPaymentDao, CustomerPaymentDaoImpl},
+    db::{create_db_pool, get_client},
+    escrow_service,
+    image_dao::{ImageDao, ImageDaoImpl},
+    investment_dao::{InvestmentDao, InvestmentDaoImpl},
+    investor_service,
+    migrations::migrate,
+    project_dao::ProjectDaoImpl,
+    project_metadata_dao::{ProjectMetadata, ProjectMetadataDao, ProjectMetadataDaoImpl},
+    project_service::{
+        self, ListProjectsParams, ProjectImagesJson, ProjectSearchResultJson, ProjectViewJson,
+        ProjectViewWithSlug, ProjectsPageJson, SearchProjectsParams, UpdateProjectJson,
+    },
+    project_stats_dao::{ProjectStatsDao, ProjectStatsDaoImpl},
+    project_stats_service,
+    revenue_service::{self, RevenueParams},
+    share_holder_dao::{ShareHolderDao, ShareHolderDaoImpl},
+    share_holder_service::{self, ShareHoldersParams},
+    staking_dao::{StakingDao, StakingDaoImpl},
+    submission_service::{self, SubmitTxsJson},
+    submitted_tx_dao::{SubmittedTxDao, SubmittedTxDaoImpl},
+    transaction_service::{self, BuildTxsJson},
+    withdrawal_dao::{WithdrawalDao, WithdrawalDaoImpl},
+    withdrawal_service::{self, WithdrawalDescriptionJson},
+};
+use crate::error::{handle_rejection, ServiceError};
+use crate::images::{ImageJson, ImageVariant};
+use crate::indexing::{start_indexing, IndexingDaos};
+use crate::relay::start_tracking;
+use crate::storage::{create_blob_store, BlobStore};
+use crate::validation::validate_project;
 use dotenv::dotenv;
-use std::env;
+use structopt::StructOpt;
 
+mod auth;
+mod chain;
+mod config;
 mod dao;
+mod error;
+mod images;
+mod indexing;
 mod logger;
+mod markdown;
+mod relay;
+mod search;
+mod slug;
+mod storage;
+mod teal;
+#[cfg(test)]
+mod test_data;
+mod validation;
+
+/// Room for the multipart boundaries and headers, on top of the max image size
+const MULTIPART_OVERHEAD_BYTES: u64 = 64 * 1024;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    init_logger();
+    dotenv().ok();
+    let opt = Opt::from_args();
+    let config = Arc::new(Config::load(&opt)?);
+
+    init_logger(&config.log_config_path);
+    log::info!(
+        "Config: bind address: {}, frontend: {}, cors origins: {:?}, tls: {}, db: {}@{}:{}/{}",
+        config.bind_address,
+        config.frontend_base_url,
+        config.cors_origins,
+        config.tls.is_some(),
+        config.db.user,
+        config.db.host,
+        config.db.port,
+        config.db.dbname
+    );
+
+    let db_pool = create_db_pool(&config.db)?;
+    migrate(&mut *get_client(&db_pool).await?).await?;
+    if let Some(Command::Migrate) = opt.command {
+        return Ok(());
+    }
 
-    let db_client = Arc::new(create_db_client().await?);
     let project_dao: Arc<dyn ProjectDao> = Arc::new(ProjectDaoImpl {
-        client: db_client.clone(),
+        pool: db_pool.clone(),
+    });
+    let image_dao: Arc<dyn ImageDao> = Arc::new(ImageDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let project_metadata_dao: Arc<dyn ProjectMetadataDao> = Arc::new(ProjectMetadataDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let auth_dao: Arc<dyn AuthDao> = Arc::new(AuthDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let investment_dao: Arc<dyn InvestmentDao> = Arc::new(InvestmentDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let customer_payment_dao: Arc<dyn CustomerPaymentDao> = Arc::new(CustomerPaymentDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let withdrawal_dao: Arc<dyn WithdrawalDao> = Arc::new(WithdrawalDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let staking_dao: Arc<dyn StakingDao> = Arc::new(StakingDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let share_holder_dao: Arc<dyn ShareHolderDao> = Arc::new(ShareHolderDaoImpl {
+        pool: db_pool.clone(),
+    });
+
+    let project_stats_dao: Arc<dyn ProjectStatsDao> = Arc::new(ProjectStatsDaoImpl {
+        pool: db_pool.clone(),
+    });
+    let submitted_tx_dao: Arc<dyn SubmittedTxDao> = Arc::new(SubmittedTxDaoImpl {
+        pool: db_pool.clone(),
     });
-    project_dao.init().await?;
 
-    let env = environment();
+    let blob_store = create_blob_store(&config.storage)?;
+
+    let algod = Arc::new(create_algod(&config.chain)?);
+    let project_verifier: Arc<dyn ProjectVerifier> = if config.chain.verify_projects {
+        Arc::new(ProjectVerifierImpl {
+            algod: algod.clone(),
+        })
+    } else {
+        log::warn!("Projects are not verified against the chain");
+        Arc::new(NoopProjectVerifier {})
+    };
+
+    let indexer: Arc<dyn ChainIndexer> = Arc::new(create_indexer(&config.chain)?);
+
+    let tx_submitter: Arc<dyn TxSubmitter> = algod.clone();
+    start_tracking(
+        &config.relay,
+        tx_submitter.clone(),
+        indexer.clone(),
+        submitted_tx_dao.clone(),
+    );
+
+    if config.indexing.enabled {
+        start_indexing(
+            &config.indexing,
+            indexer.clone(),
+            IndexingDaos {
+                project_dao: project_dao.clone(),
+                investment_dao,
+                customer_payment_dao: customer_payment_dao.clone(),
+                withdrawal_dao: withdrawal_dao.clone(),
+                staking_dao: staking_dao.clone(),
+                share_holder_dao: share_holder_dao.clone(),
+                project_stats_dao: project_stats_dao.clone(),
+            },
+        );
+    } else {
+        log::warn!("Indexing is disabled");
+    }
 
     let cors = warp::cors()
-        .allow_origin(frontend_host(&env))
+        .allow_origins(config.cors_origins.iter().map(String::as_str))
         .allow_headers(vec![
             "User-Agent",
             "Sec-Fetch-Mode",
@@ -45,77 +200,449 @@ async fn main() -> Result<()> {
             "Accept",
             "Access-Control-Request-Method",
             "Access-Control-Request-Headers",
+            "Authorization",
         ])
-        .allow_methods(vec!["GET", "POST"]);
+        .allow_methods(vec!["GET", "POST", "PUT", "DELETE"]);
 
-    // TODO path project/save
-    let save_project = warp::post()
-        .and(warp::path!("save"))
+    let create_challenge = warp::post()
+        .and(warp::path!("auth" / "challenge"))
         .and(warp::body::json())
-        .and(with_env(env.clone()))
-        .and(with_project_dao(project_dao.clone()))
-        .and_then(|p: ProjectJson, env, dao: Arc<dyn ProjectDao>| async {
-            handle_save_project(dao, env, p).await
-        })
-        .with(cors.clone())
-        .with(warp::log("post save_project log"));
+        .and(with_config(config.clone()))
+        .and(with_auth_dao(auth_dao.clone()))
+        .and_then(
+            |r: ChallengeRequestJson, config, dao: Arc<dyn AuthDao>| async move {
+                handle_create_challenge(dao, config, r).await
+            },
+        )
+        .with(warp::log("post create_challenge log"));
 
-    // project "view" for UI. TODO rename
+    let verify_challenge = warp::post()
+        .and(warp::path!("auth" / "verify"))
+        .and(warp::body::json())
+        .and(with_config(config.clone()))
+        .and(with_auth_dao(auth_dao.clone()))
+        .and_then(
+            |r: VerifyChallengeJson, config, dao: Arc<dyn AuthDao>| async move {
+                handle_verify_challenge(dao, config, r).await
+            },
+        )
+        .with(warp::log("post verify_challenge log"));
+
+    // TODO path project/save
+    let save_project =
+        warp::post()
+            .and(warp::path!("save"))
+            .and(with_auth(auth_dao.clone()))
+            .and(warp::body::json())
+            .and(with_config(config.clone()))
+            .and(with_project_dao(project_dao.clone()))
+            .and(with_project_verifier(project_verifier))
+            .and_then(
+                |address: Address,
+                 p: SaveProjectJson,
+                 config,
+                 dao: Arc<dyn ProjectDao>,
+                 verifier| async move {
+                    handle_save_project(dao, verifier, config, address, p).await
+                },
+            )
+            .with(warp::log("post save_project log"));
+
+    // deprecated, redirects to invest_with_uuid
     let invest_project = warp::get()
         .and(warp::path!("invest" / String))
-        .and(with_env(env.clone()))
         .and(with_project_dao(project_dao.clone()))
-        .and_then(|id: String, env, dao: Arc<dyn ProjectDao>| async {
-            handle_get_project_for_users(dao, env, id).await
+        .and_then(|id: String, dao: Arc<dyn ProjectDao>| async {
+            handle_legacy_project_redirect(dao, "invest_with_uuid", id).await
         })
-        .with(cors.clone())
         .with(warp::log("get invest_project log"));
 
     // project "view" for UI. TODO rename
     let invest_project_with_uuid = warp::get()
         .and(warp::path!("invest_with_uuid" / String))
-        .and(with_env(env.clone()))
+        .and(with_config(config.clone()))
         .and(with_project_dao(project_dao.clone()))
-        .and_then(|id: String, env, dao: Arc<dyn ProjectDao>| async {
-            handle_get_project_for_users_with_uuid(dao, env, id).await
-        })
-        .with(cors.clone())
+        .and(with_project_metadata_dao(project_metadata_dao.clone()))
+        .and(with_project_stats_dao(project_stats_dao.clone()))
+        .and_then(
+            |uuid: String, config, dao: Arc<dyn ProjectDao>, metadata_dao, stats_dao| async {
+                handle_get_project_view(dao, metadata_dao, stats_dao, config, uuid).await
+            },
+        )
         .with(warp::log("get invest_project_with_uuid log"));
 
+    // deprecated, redirects to project_with_uuid
     let load_project = warp::get()
         .and(warp::path!("project" / String))
         .and(with_project_dao(project_dao.clone()))
         .and_then(|id: String, dao: Arc<dyn ProjectDao>| async {
-            handle_get_project(dao, id).await
+            handle_legacy_project_redirect(dao, "project_with_uuid", id).await
         })
-        .with(cors.clone())
         .with(warp::log("get load_project log"));
 
+    // project "view" for UI, with the slug of the links
+    let project_with_slug = warp::get()
+        .and(warp::path!("p" / String))
+        .and(with_config(config.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_project_metadata_dao(project_metadata_dao.clone()))
+        .and(with_project_stats_dao(project_stats_dao.clone()))
+        .and_then(
+            |slug: String, config, dao: Arc<dyn ProjectDao>, metadata_dao, stats_dao| async {
+                handle_get_project_view_with_slug(dao, metadata_dao, stats_dao, config, slug).await
+            },
+        )
+        .with(warp::log("get project_with_slug log"));
+
+    let list_projects = warp::get()
+        .and(warp::path!("projects"))
+        .and(warp::query::<ListProjectsParams>())
+        .and(with_config(config.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and_then(
+            |params: ListProjectsParams, config, dao: Arc<dyn ProjectDao>| async move {
+                handle_list_projects(dao, config, params).await
+            },
+        )
+        .with(warp::log("get list_projects log"));
+
+    let load_revenue = warp::get()
+        .and(warp::path!("projects" / String / "revenue"))
+        .and(warp::query::<RevenueParams>())
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_customer_payment_dao(customer_payment_dao.clone()))
+        .and_then(
+            |uuid: String, params: RevenueParams, dao: Arc<dyn ProjectDao>, payment_dao| async move {
+                handle_load_revenue(dao, payment_dao, uuid, params).await
+            },
+        )
+        .with(warp::log("get load_revenue log"));
+
+    let load_withdrawals = warp::get()
+        .and(warp::path!("projects" / String / "withdrawals"))
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_withdrawal_dao(withdrawal_dao.clone()))
+        .and_then(
+            |uuid: String, dao: Arc<dyn ProjectDao>, withdrawal_dao| async move {
+                handle_load_withdrawals(dao, withdrawal_dao, uuid).await
+            },
+        )
+        .with(warp::log("get load_withdrawals log"));
+
+    let describe_withdrawal = warp::put()
+        .and(warp::path!("projects" / String / "withdrawals" / String))
+        .and(with_auth(auth_dao.clone()))
+        .and(warp::body::json())
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_withdrawal_dao(withdrawal_dao))
+        .and_then(
+            |uuid: String,
+             txid: String,
+             address: Address,
+             json: WithdrawalDescriptionJson,
+             dao: Arc<dyn ProjectDao>,
+             withdrawal_dao| async move {
+                handle_describe_withdrawal(dao, withdrawal_dao, address, uuid, txid, json).await
+            },
+        )
+        .with(warp::log("put describe_withdrawal log"));
+
+    let load_investor_project = warp::get()
+        .and(warp::path!("investors" / String / "projects" / String))
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_staking_dao(staking_dao))
+        .and(with_customer_payment_dao(customer_payment_dao))
+        .and(with_indexer(indexer))
+        .and_then(
+            |address: String,
+             uuid: String,
+             dao: Arc<dyn ProjectDao>,
+             staking_dao,
+             customer_payment_dao,
+             indexer| async move {
+                handle_load_investor_project(
+                    dao,
+                    staking_dao,
+                    customer_payment_dao,
+                    indexer,
+                    address,
+                    uuid,
+                )
+                .await
+            },
+        )
+        .with(warp::log("get load_investor_project log"));
+
+    let load_share_holders = warp::get()
+        .and(warp::path!("projects" / String / "holders"))
+        .and(warp::query::<ShareHoldersParams>())
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_share_holder_dao(share_holder_dao))
+        .and_then(
+            |uuid: String,
+             params: ShareHoldersParams,
+             dao: Arc<dyn ProjectDao>,
+             share_holder_dao| async move {
+                handle_load_share_holders(dao, share_holder_dao, uuid, params).await
+            },
+        )
+        .with(warp::log("get load_share_holders log"));
+
+    let load_project_stats = warp::get()
+        .and(warp::path!("projects" / String / "stats"))
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_project_stats_dao(project_stats_dao))
+        .and_then(
+            |uuid: String, dao: Arc<dyn ProjectDao>, stats_dao| async move {
+                handle_load_project_stats(dao, stats_dao, uuid).await
+            },
+        )
+        .with(warp::log("get load_project_stats log"));
+
+    let load_escrow = warp::get()
+        .and(warp::path!("projects" / String / "escrows" / String))
+        .and(with_project_dao(project_dao.clone()))
+        .and_then(
+            |uuid: String, kind: String, dao: Arc<dyn ProjectDao>| async move {
+                handle_load_escrow(dao, uuid, kind).await
+            },
+        )
+        .with(warp::log("get load_escrow log"));
+
+    // unsigned transaction groups, for the user to sign and submit
+    let build_txs = warp::post()
+        .and(warp::path!("projects" / String / "txs" / String))
+        .and(warp::body::json())
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_algod(algod.clone()))
+        .and_then(
+            |uuid: String,
+             action: String,
+             body: BuildTxsJson,
+             dao: Arc<dyn ProjectDao>,
+             algod: Arc<Algod>| async move {
+                handle_build_txs(dao, algod, uuid, action, body).await
+            },
+        )
+        .with(warp::log("post build_txs log"));
+
+    let submit_txs = warp::post()
+        .and(warp::path!("tx" / "submit"))
+        .and(warp::body::json())
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_submitted_tx_dao(submitted_tx_dao.clone()))
+        .and(with_tx_submitter(tx_submitter))
+        .and_then(
+            |body: SubmitTxsJson, dao: Arc<dyn ProjectDao>, submitted_tx_dao, submitter| async move {
+                handle_submit_txs(dao, submitted_tx_dao, submitter, body).await
+            },
+        )
+        .with(warp::log("post submit_txs log"));
+
+    let load_submitted_tx = warp::get()
+        .and(warp::path!("tx" / String))
+        .and(with_submitted_tx_dao(submitted_tx_dao))
+        .and_then(|txid: String, dao: Arc<dyn SubmittedTxDao>| async move {
+            handle_load_submitted_tx(dao, txid).await
+        })
+        .with(warp::log("get load_submitted_tx log"));
+
+    let search_projects = warp::get()
+        .and(warp::path!("projects" / "search"))
+        .and(warp::query::<SearchProjectsParams>())
+        .and(with_config(config.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and_then(
+            |params: SearchProjectsParams, config, dao: Arc<dyn ProjectDao>| async move {
+                handle_search_projects(dao, config, params).await
+            },
+        )
+        .with(warp::log("get search_projects log"));
+
+    let update_project = warp::put()
+        .and(warp::path!("project_with_uuid" / String))
+        .and(with_auth(auth_dao.clone()))
+        .and(warp::body::json())
+        .and(with_config(config.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and_then(
+            |uuid: String,
+             address: Address,
+             update: UpdateProjectJson,
+             config,
+             dao: Arc<dyn ProjectDao>| async move {
+                handle_update_project(dao, config, address, uuid, update).await
+            },
+        )
+        .with(warp::log("put update_project log"));
+
+    let update_project_metadata = warp::put()
+        .and(warp::path!("project_with_uuid" / String / "metadata"))
+        .and(with_auth(auth_dao.clone()))
+        .and(warp::body::json())
+        .and(with_config(config.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_project_metadata_dao(project_metadata_dao.clone()))
+        .and(with_project_stats_dao(project_stats_dao.clone()))
+        .and_then(
+            |uuid: String,
+             address: Address,
+             metadata: ProjectMetadata,
+             config,
+             dao: Arc<dyn ProjectDao>,
+             metadata_dao,
+             stats_dao| async move {
+                handle_update_project_metadata(
+                    dao,
+                    metadata_dao,
+                    stats_dao,
+                    config,
+                    address,
+                    uuid,
+                    metadata,
+                )
+                .await
+            },
+        )
+        .with(warp::log("put update_project_metadata log"));
+
+    let update_project_images = warp::put()
+        .and(warp::path!("project_with_uuid" / String / "images"))
+        .and(with_auth(auth_dao.clone()))
+        .and(warp::body::json())
+        .and(with_config(config.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and(with_project_metadata_dao(project_metadata_dao))
+        .and(with_image_dao(image_dao.clone()))
+        .and(with_project_stats_dao(project_stats_dao.clone()))
+        .and_then(
+            |uuid: String,
+             address: Address,
+             images: ProjectImagesJson,
+             config,
+             dao: Arc<dyn ProjectDao>,
+             metadata_dao,
+             image_dao,
+             stats_dao| async move {
+                handle_update_project_images(
+                    dao,
+                    metadata_dao,
+                    image_dao,
+                    stats_dao,
+                    config,
+                    address,
+                    uuid,
+                    images,
+                )
+                .await
+            },
+        )
+        .with(warp::log("put update_project_images log"));
+
+    let upload_image = warp::post()
+        .and(warp::path!("images"))
+        .and(with_auth(auth_dao.clone()))
+        .and(
+            warp::multipart::form()
+                .max_length(config.images.max_size_bytes + MULTIPART_OVERHEAD_BYTES),
+        )
+        .and(with_config(config.clone()))
+        .and(with_image_dao(image_dao.clone()))
+        .and(with_blob_store(blob_store.clone()))
+        .and_then(
+            |address: Address, form: FormData, config, dao, blob_store| async move {
+                handle_upload_image(dao, blob_store, config, address, form).await
+            },
+        )
+        .with(warp::log("post upload_image log"));
+
+    let load_image = warp::get()
+        .and(warp::path!("images" / String))
+        .and(with_image_dao(image_dao.clone()))
+        .and(with_blob_store(blob_store.clone()))
+        .and_then(|id: String, dao, blob_store| async move {
+            handle_get_image(dao, blob_store, id, ImageVariant::Original).await
+        })
+        .with(warp::log("get load_image log"));
+
+    let load_thumbnail = warp::get()
+        .and(warp::path!("images" / String / "thumbnail"))
+        .and(with_image_dao(image_dao))
+        .and(with_blob_store(blob_store))
+        .and_then(|id: String, dao, blob_store| async move {
+            handle_get_image(dao, blob_store, id, ImageVariant::Thumbnail).await
+        })
+        .with(warp::log("get load_thumbnail log"));
+
+    let archive_project = warp::delete()
+        .and(warp::path!("project_with_uuid" / String))
+        .and(with_auth(auth_dao.clone()))
+        .and(with_project_dao(project_dao.clone()))
+        .and_then(
+            |uuid: String, address: Address, dao: Arc<dyn ProjectDao>| async move {
+                handle_archive_project(dao, address, uuid).await
+            },
+        )
+        .with(warp::log("delete archive_project log"));
+
     let load_project_with_uuid = warp::get()
         .and(warp::path!("project_with_uuid" / String))
         .and(with_project_dao(project_dao))
-        .and_then(|id: String, dao: Arc<dyn ProjectDao>| async {
-            handle_get_project_with_uuid(dao, id).await
+        .and_then(|uuid: String, dao: Arc<dyn ProjectDao>| async {
+            handle_get_project(dao, uuid).await
         })
-        .with(cors.clone())
-        .with(warp::log("get load_project log"));
+        .with(warp::log("get load_project_with_uuid log"));
 
-    warp::serve(
-        save_project
-            .or(invest_project)
-            .or(invest_project_with_uuid)
-            .or(load_project)
-            .or(load_project_with_uuid),
-    )
-    // .run(([127, 0, 0, 1], 3030))
-    .run(([0, 0, 0, 0], 3030))
-    .await;
+    // cors last, so error responses get the headers too
+    let routes = create_challenge
+        .or(verify_challenge)
+        .or(save_project)
+        .or(invest_project)
+        .or(invest_project_with_uuid)
+        .or(load_project)
+        .or(load_project_with_uuid)
+        .or(project_with_slug)
+        .or(list_projects)
+        .or(search_projects)
+        .or(load_revenue)
+        .or(load_withdrawals)
+        .or(describe_withdrawal)
+        .or(load_investor_project)
+        .or(load_share_holders)
+        .or(load_project_stats)
+        .or(load_escrow)
+        .or(build_txs)
+        .or(submit_txs)
+        .or(load_submitted_tx)
+        .or(update_project)
+        .or(update_project_metadata)
+        .or(update_project_images)
+        .or(upload_image)
+        .or(load_image)
+        .or(load_thumbnail)
+        .or(archive_project)
+        .recover(handle_rejection)
+        .with(cors);
+
+    match &config.tls {
+        Some(tls) => {
+            warp::serve(routes)
+                .tls()
+                .cert_path(&tls.cert_path)
+                .key_path(&tls.key_path)
+                .run(config.bind_address)
+                .await
+        }
+        None => warp::serve(routes).run(config.bind_address).await,
+    }
 
     Ok(())
 }
 
-fn with_env(env: Env) -> impl Filter<Extract = (Env,), Error = std::convert::Infallible> + Clone {
-    warp::any().map(move || env.clone())
+fn with_config(
+    config: Arc<Config>,
+) -> impl Filter<Extract = (Arc<Config>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || config.clone())
 }
 
 fn with_project_dao(
@@ -124,87 +651,566 @@ fn with_project_dao(
     warp::any().map(move || dao.clone())
 }
 
+fn with_project_metadata_dao(
+    dao: Arc<dyn ProjectMetadataDao>,
+) -> impl Filter<Extract = (Arc<dyn ProjectMetadataDao>,), Error = std::convert::Infallible> + Clone
+{
+    warp::any().map(move || dao.clone())
+}
+
+fn with_image_dao(
+    dao: Arc<dyn ImageDao>,
+) -> impl Filter<Extract = (Arc<dyn ImageDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+fn with_customer_payment_dao(
+    dao: Arc<dyn CustomerPaymentDao>,
+) -> impl Filter<Extract = (Arc<dyn CustomerPaymentDao>,), Error = std::convert::Infallible> + Clone
+{
+    warp::any().map(move || dao.clone())
+}
+
+fn with_withdrawal_dao(
+    dao: Arc<dyn WithdrawalDao>,
+) -> impl Filter<Extract = (Arc<dyn WithdrawalDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+fn with_staking_dao(
+    dao: Arc<dyn StakingDao>,
+) -> impl Filter<Extract = (Arc<dyn StakingDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+fn with_share_holder_dao(
+    dao: Arc<dyn ShareHolderDao>,
+) -> impl Filter<Extract = (Arc<dyn ShareHolderDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+fn with_project_stats_dao(
+    dao: Arc<dyn ProjectStatsDao>,
+) -> impl Filter<Extract = (Arc<dyn ProjectStatsDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+fn with_algod(
+    algod: Arc<Algod>,
+) -> impl Filter<Extract = (Arc<Algod>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || algod.clone())
+}
+
+fn with_submitted_tx_dao(
+    dao: Arc<dyn SubmittedTxDao>,
+) -> impl Filter<Extract = (Arc<dyn SubmittedTxDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+fn with_tx_submitter(
+    submitter: Arc<dyn TxSubmitter>,
+) -> impl Filter<Extract = (Arc<dyn TxSubmitter>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || submitter.clone())
+}
+
+fn with_indexer(
+    indexer: Arc<dyn ChainIndexer>,
+) -> impl Filter<Extract = (Arc<dyn ChainIndexer>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || indexer.clone())
+}
+
+fn with_blob_store(
+    blob_store: Arc<dyn BlobStore>,
+) -> impl Filter<Extract = (Arc<dyn BlobStore>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || blob_store.clone())
+}
+
+fn with_auth_dao(
+    dao: Arc<dyn AuthDao>,
+) -> impl Filter<Extract = (Arc<dyn AuthDao>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || dao.clone())
+}
+
+/// Extracts the address of the session in the "Authorization: Bearer <token>" header.
+/// Rejects with unauthorized if there's no valid session.
+fn with_auth(
+    dao: Arc<dyn AuthDao>,
+) -> impl Filter<Extract = (Address,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(with_auth_dao(dao))
+        .and_then(|header: Option<String>, dao: Arc<dyn AuthDao>| async move {
+            let token = header
+                .as_deref()
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .ok_or_else(|| {
+                    warp::reject::custom(ServiceError::Unauthorized(
+                        "Missing bearer token".to_owned(),
+                    ))
+                })?;
+            session::authenticate(&*dao, token)
+                .await
+                .map_err(warp::reject::custom)
+        })
+}
+
+fn with_project_verifier(
+    verifier: Arc<dyn ProjectVerifier>,
+) -> impl Filter<Extract = (Arc<dyn ProjectVerifier>,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || verifier.clone())
+}
+
+#[derive(Deserialize)]
+struct SaveProjectJson {
+    project: ProjectJson,
+    /// Creator's signature (base64) of auth::signature::project_signing_data
+    signature: String,
+}
+
+async fn handle_create_challenge(
+    auth_dao: Arc<dyn AuthDao>,
+    config: Arc<Config>,
+    request: ChallengeRequestJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = session::create_challenge(&*auth_dao, &config.auth, &request).await;
+    log::debug!("handle_create_challenge res: {:?}", res);
+    Ok(warp::reply::json(&res.map_err(warp::reject::custom)?))
+}
+
+async fn handle_verify_challenge(
+    auth_dao: Arc<dyn AuthDao>,
+    config: Arc<Config>,
+    request: VerifyChallengeJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let session = session::verify_challenge(&*auth_dao, &config.auth, &request)
+        .await
+        .map_err(warp::reject::custom)?;
+    // don't log the token
+    log::debug!(
+        "handle_verify_challenge: session expires at: {}",
+        session.expires_at
+    );
+    Ok(warp::reply::json(&session))
+}
+
 async fn handle_save_project(
     project_dao: Arc<dyn ProjectDao>,
-    env: Env,
-    project: ProjectJson,
-) -> Result<impl warp::Reply, Infallible> {
-    let project: Project = project.try_into().unwrap();
+    project_verifier: Arc<dyn ProjectVerifier>,
+    config: Arc<Config>,
+    address: Address,
+    save_project: SaveProjectJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let project = validate_project(save_project.project).map_err(warp::reject::custom)?;
     log::debug!("got project: {:?}", project);
 
-    let res = project_service::save_project(&*project_dao, &env, &project).await;
+    if project.creator != address {
+        return Err(warp::reject::custom(ServiceError::Forbidden(
+            "Only the creator can save the project".to_owned(),
+        )));
+    }
+
+    let res = project_service::save_project(
+        &*project_dao,
+        &*project_verifier,
+        &config,
+        &project,
+        &save_project.signature,
+    )
+    .await;
     log::debug!("handle_save_project res: {:?}", res);
     project_for_users_json(res)
 }
 
-async fn handle_get_project_for_users(
+async fn handle_get_project_view(
     project_dao: Arc<dyn ProjectDao>,
-    env: Env,
+    project_metadata_dao: Arc<dyn ProjectMetadataDao>,
+    project_stats_dao: Arc<dyn ProjectStatsDao>,
+    config: Arc<Config>,
+    uuid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::load_project_view(
+        &*project_dao,
+        &*project_metadata_dao,
+        &*project_stats_dao,
+        &config,
+        &uuid,
+    )
+    .await;
+    log::debug!("handle_get_project_view res: {:?}", res);
+    let view = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectViewJson::from(view)))
+}
+
+/// Previous slugs are permanently redirected to the current one
+async fn handle_get_project_view_with_slug(
+    project_dao: Arc<dyn ProjectDao>,
+    project_metadata_dao: Arc<dyn ProjectMetadataDao>,
+    project_stats_dao: Arc<dyn ProjectStatsDao>,
+    config: Arc<Config>,
+    slug: String,
+) -> Result<Box<dyn warp::Reply>, Rejection> {
+    let res = project_service::load_project_view_with_slug(
+        &*project_dao,
+        &*project_metadata_dao,
+        &*project_stats_dao,
+        &config,
+        &slug,
+    )
+    .await;
+    log::debug!("handle_get_project_view_with_slug res: {:?}", res);
+    match res.map_err(warp::reject::custom)? {
+        ProjectViewWithSlug::Found(view) => {
+            Ok(Box::new(warp::reply::json(&ProjectViewJson::from(view))))
+        }
+        ProjectViewWithSlug::Moved(current_slug) => Ok(Box::new(warp::redirect::permanent(
+            to_uri(&format!("/p/{}", current_slug))?,
+        ))),
+    }
+}
+
+async fn handle_update_project_images(
+    project_dao: Arc<dyn ProjectDao>,
+    project_metadata_dao: Arc<dyn ProjectMetadataDao>,
+    image_dao: Arc<dyn ImageDao>,
+    project_stats_dao: Arc<dyn ProjectStatsDao>,
+    config: Arc<Config>,
+    address: Address,
+    uuid: String,
+    images: ProjectImagesJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::update_project_images(
+        &*project_dao,
+        &*project_metadata_dao,
+        &*image_dao,
+        &*project_stats_dao,
+        &config,
+        &address,
+        &uuid,
+        &images,
+    )
+    .await;
+    log::debug!("handle_update_project_images res: {:?}", res);
+    let view = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectViewJson::from(view)))
+}
+
+async fn handle_upload_image(
+    image_dao: Arc<dyn ImageDao>,
+    blob_store: Arc<dyn BlobStore>,
+    config: Arc<Config>,
+    address: Address,
+    form: FormData,
+) -> Result<impl warp::Reply, Rejection> {
+    let bytes = read_image_part(form).await.map_err(warp::reject::custom)?;
+    let res =
+        images::upload_image(&*image_dao, &*blob_store, &config.images, &address, bytes).await;
+    log::debug!("handle_upload_image res: {:?}", res);
+    let image = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::with_status(
+        warp::reply::json(&ImageJson::from(image)),
+        StatusCode::CREATED,
+    ))
+}
+
+/// The content of the form's "image" part
+async fn read_image_part(form: FormData) -> Result<Vec<u8>, ServiceError> {
+    let invalid_form = |e: warp::Error| ServiceError::InvalidInput(format!("Invalid form: {}", e));
+    let parts: Vec<Part> = form.try_collect().await.map_err(invalid_form)?;
+    let part = parts
+        .into_iter()
+        .find(|part| part.name() == "image")
+        .ok_or_else(|| ServiceError::InvalidInput("Missing form part: image".to_owned()))?;
+    part.stream()
+        .try_fold(vec![], |mut bytes, data| async move {
+            bytes.extend_from_slice(data.chunk());
+            Ok(bytes)
+        })
+        .await
+        .map_err(invalid_form)
+}
+
+/// Image content doesn't change (new content gets a new id), so it can be cached indefinitely
+async fn handle_get_image(
+    image_dao: Arc<dyn ImageDao>,
+    blob_store: Arc<dyn BlobStore>,
     id: String,
-) -> Result<impl warp::Reply, Infallible> {
-    let res = project_service::load_project_for_users(&*project_dao, &env, &id).await;
-    log::debug!("handle_get_project_for_users res: {:?}", res);
-    project_for_users_json(res)
+    variant: ImageVariant,
+) -> Result<impl warp::Reply, Rejection> {
+    let (content_type, bytes) = images::load_image_content(&*image_dao, &*blob_store, &id, variant)
+        .await
+        .map_err(warp::reject::custom)?;
+    Response::builder()
+        .header(CONTENT_TYPE, content_type)
+        .header(CACHE_CONTROL, "public, max-age=31536000, immutable")
+        .body(bytes)
+        .map_err(|e| warp::reject::custom(ServiceError::Internal(e.to_string())))
 }
 
-async fn handle_get_project_for_users_with_uuid(
+async fn handle_update_project_metadata(
     project_dao: Arc<dyn ProjectDao>,
-    env: Env,
+    project_metadata_dao: Arc<dyn ProjectMetadataDao>,
+    project_stats_dao: Arc<dyn ProjectStatsDao>,
+    config: Arc<Config>,
+    address: Address,
     uuid: String,
-) -> Result<impl warp::Reply, Infallible> {
-    let res = project_service::load_project_for_users_with_uuid(&*project_dao, &env, &uuid).await;
-    log::debug!("handle_get_project_for_users res: {:?}", res);
-    project_for_users_json(res)
+    metadata: ProjectMetadata,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::update_project_metadata(
+        &*project_dao,
+        &*project_metadata_dao,
+        &*project_stats_dao,
+        &config,
+        &address,
+        &uuid,
+        metadata,
+    )
+    .await;
+    log::debug!("handle_update_project_metadata res: {:?}", res);
+    let view = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectViewJson::from(view)))
 }
 
 async fn handle_get_project(
     project_dao: Arc<dyn ProjectDao>,
-    id: String,
-) -> Result<impl warp::Reply, Infallible> {
-    let res = project_service::load_project(&*project_dao, &id).await;
+    uuid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::load_project(&*project_dao, &uuid).await;
     log::debug!("handle_get_project res: {:?}", res);
     project_json(res)
 }
 
-async fn handle_get_project_with_uuid(
+/// Permanent redirect from the deprecated id based routes to the uuid based `route`
+async fn handle_legacy_project_redirect(
+    project_dao: Arc<dyn ProjectDao>,
+    route: &str,
+    id: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let uuid = project_service::load_legacy_project_uuid(&*project_dao, &id)
+        .await
+        .map_err(warp::reject::custom)?;
+    Ok(warp::reply::with_header(
+        warp::redirect::permanent(to_uri(&format!("/{}/{}", route, uuid))?),
+        "Deprecation",
+        "true",
+    ))
+}
+
+async fn handle_list_projects(
+    project_dao: Arc<dyn ProjectDao>,
+    config: Arc<Config>,
+    params: ListProjectsParams,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::list_projects(&*project_dao, &config, &params).await;
+    log::debug!("handle_list_projects res: {:?}", res);
+    let page = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectsPageJson::from(page)))
+}
+
+#[derive(Serialize)]
+struct ProjectSearchResultsJson {
+    projects: Vec<ProjectSearchResultJson>,
+}
+
+async fn handle_load_revenue(
     project_dao: Arc<dyn ProjectDao>,
+    customer_payment_dao: Arc<dyn CustomerPaymentDao>,
     uuid: String,
-) -> Result<impl warp::Reply, Infallible> {
-    let res = project_service::load_project_with_uuid(&*project_dao, &uuid).await;
-    log::debug!("handle_get_project res: {:?}", res);
-    project_json(res)
+    params: RevenueParams,
+) -> Result<impl warp::Reply, Rejection> {
+    let res =
+        revenue_service::load_revenue(&*project_dao, &*customer_payment_dao, &uuid, &params).await;
+    log::debug!("handle_load_revenue res: {:?}", res);
+    let revenue = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&revenue))
 }
 
-fn project_for_users_json(res: Result<ProjectForUsers>) -> Result<impl warp::Reply, Infallible> {
-    let json_res = res
-        .map(ProjectForUsersJson::from)
-        .map_err(|e| e.to_string());
-    Ok(warp::reply::json(&json_res))
+async fn handle_load_withdrawals(
+    project_dao: Arc<dyn ProjectDao>,
+    withdrawal_dao: Arc<dyn WithdrawalDao>,
+    uuid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = withdrawal_service::load_withdrawals(&*project_dao, &*withdrawal_dao, &uuid).await;
+    log::debug!("handle_load_withdrawals res: {:?}", res);
+    let withdrawals = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&withdrawals))
 }
 
-fn project_json(res: Result<Project>) -> Result<impl warp::Reply, Infallible> {
-    let json_res = res.map(ProjectJson::from).map_err(|e| e.to_string());
-    Ok(warp::reply::json(&json_res))
+async fn handle_describe_withdrawal(
+    project_dao: Arc<dyn ProjectDao>,
+    withdrawal_dao: Arc<dyn WithdrawalDao>,
+    address: Address,
+    uuid: String,
+    txid: String,
+    json: WithdrawalDescriptionJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = withdrawal_service::save_withdrawal_description(
+        &*project_dao,
+        &*withdrawal_dao,
+        &address,
+        &uuid,
+        &txid,
+        &json,
+    )
+    .await;
+    log::debug!("handle_describe_withdrawal res: {:?}", res);
+    res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::with_status(
+        warp::reply(),
+        warp::http::StatusCode::NO_CONTENT,
+    ))
 }
 
-fn frontend_host(env: &Env) -> &'static str {
-    match env {
-        Env::Local => "http://localhost:3000",
-        Env::Test => "http://test.app.capi.money",
-    }
+async fn handle_load_investor_project(
+    project_dao: Arc<dyn ProjectDao>,
+    staking_dao: Arc<dyn StakingDao>,
+    customer_payment_dao: Arc<dyn CustomerPaymentDao>,
+    indexer: Arc<dyn ChainIndexer>,
+    address: String,
+    uuid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = investor_service::load_investor_project(
+        &*project_dao,
+        &*staking_dao,
+        &*customer_payment_dao,
+        &*indexer,
+        &address,
+        &uuid,
+    )
+    .await;
+    log::debug!("handle_load_investor_project res: {:?}", res);
+    let investor_project = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&investor_project))
+}
+
+async fn handle_load_share_holders(
+    project_dao: Arc<dyn ProjectDao>,
+    share_holder_dao: Arc<dyn ShareHolderDao>,
+    uuid: String,
+    params: ShareHoldersParams,
+) -> Result<impl warp::Reply, Rejection> {
+    let res =
+        share_holder_service::load_share_holders(&*project_dao, &*share_holder_dao, &uuid, &params)
+            .await;
+    log::debug!("handle_load_share_holders res: {:?}", res);
+    let page = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&page))
 }
 
-#[derive(Debug, Clone)]
-pub enum Env {
-    Local,
-    Test,
+async fn handle_load_project_stats(
+    project_dao: Arc<dyn ProjectDao>,
+    project_stats_dao: Arc<dyn ProjectStatsDao>,
+    uuid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res =
+        project_stats_service::load_project_stats(&*project_dao, &*project_stats_dao, &uuid).await;
+    log::debug!("handle_load_project_stats res: {:?}", res);
+    let stats = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&stats))
 }
 
-fn environment() -> Env {
-    dotenv().ok();
-    let env = env::var("TEST_ENV").unwrap();
-    println!("Env value: {}", env);
-    let env = if env == "1" { Env::Test } else { Env::Local };
-    log::info!("Environment: {:?}", env);
-    env
+async fn handle_load_escrow(
+    project_dao: Arc<dyn ProjectDao>,
+    uuid: String,
+    kind: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = escrow_service::load_escrow(&*project_dao, &uuid, &kind).await;
+    log::debug!("handle_load_escrow res: {:?}", res);
+    let escrow = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&escrow))
+}
+
+async fn handle_build_txs(
+    project_dao: Arc<dyn ProjectDao>,
+    algod: Arc<Algod>,
+    uuid: String,
+    action: String,
+    body: BuildTxsJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = transaction_service::build_txs(&*project_dao, &algod, &uuid, &action, &body).await;
+    log::debug!("handle_build_txs res: {:?}", res);
+    let group = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&group))
+}
+
+async fn handle_submit_txs(
+    project_dao: Arc<dyn ProjectDao>,
+    submitted_tx_dao: Arc<dyn SubmittedTxDao>,
+    submitter: Arc<dyn TxSubmitter>,
+    body: SubmitTxsJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let res =
+        submission_service::submit_txs(&*project_dao, &*submitted_tx_dao, &*submitter, &body).await;
+    log::debug!("handle_submit_txs res: {:?}", res);
+    let submitted = res.map_err(warp::reject::custom)?;
+    // confirmation is tracked: see GET /tx/{txid}
+    Ok(warp::reply::with_status(
+        warp::reply::json(&submitted),
+        StatusCode::ACCEPTED,
+    ))
+}
+
+async fn handle_load_submitted_tx(
+    submitted_tx_dao: Arc<dyn SubmittedTxDao>,
+    txid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = submission_service::load_submitted_tx(&*submitted_tx_dao, &txid).await;
+    log::debug!("handle_load_submitted_tx res: {:?}", res);
+    let tx = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&tx))
+}
+
+async fn handle_search_projects(
+    project_dao: Arc<dyn ProjectDao>,
+    config: Arc<Config>,
+    params: SearchProjectsParams,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::search_projects(&*project_dao, &config, &params).await;
+    log::debug!("handle_search_projects res: {:?}", res);
+    let results = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectSearchResultsJson {
+        projects: results.into_iter().map(|r| r.into()).collect(),
+    }))
+}
+
+async fn handle_update_project(
+    project_dao: Arc<dyn ProjectDao>,
+    config: Arc<Config>,
+    address: Address,
+    uuid: String,
+    update: UpdateProjectJson,
+) -> Result<impl warp::Reply, Rejection> {
+    let res =
+        project_service::update_project(&*project_dao, &config, &address, &uuid, &update).await;
+    log::debug!("handle_update_project res: {:?}", res);
+    project_for_users_json(res)
+}
+
+async fn handle_archive_project(
+    project_dao: Arc<dyn ProjectDao>,
+    address: Address,
+    uuid: String,
+) -> Result<impl warp::Reply, Rejection> {
+    let res = project_service::archive_project(&*project_dao, &address, &uuid).await;
+    log::debug!("handle_archive_project res: {:?}", res);
+    res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::with_status(
+        warp::reply(),
+        warp::http::StatusCode::NO_CONTENT,
+    ))
+}
+
+fn to_uri(path: &str) -> Result<Uri, Rejection> {
+    path.parse()
+        .map_err(|e: InvalidUri| warp::reject::custom(ServiceError::Internal(e.to_string())))
+}
+
+fn project_for_users_json(
+    res: Result<ProjectForUsers, ServiceError>,
+) -> Result<impl warp::Reply, Rejection> {
+    let project = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectForUsersJson::from(project)))
+}
+
+fn project_json(res: Result<Project, ServiceError>) -> Result<impl warp::Reply, Rejection> {
+    let project = res.map_err(warp::reject::custom)?;
+    Ok(warp::reply::json(&ProjectJson::from(project)))
 }
@@ -0,0 +1,34 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Renders user provided markdown to HTML that can be embedded in the frontend:
+/// scripts, styles, event handlers etc. are removed, links get rel="noopener noreferrer".
+pub fn render_markdown(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+    ammonia::clean(&unsafe_html)
+}
+
+#[cfg(test)]
+mod test {
+    use super::render_markdown;
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_render_markdown_removes_scripts() -> Result<()> {
+        let html = render_markdown(
+            "# Hi\n\n[site](https://capi.finance) <img src=x onerror=alert(1)>\n\n<script>alert(1)</script>",
+        );
+        assert!(html.starts_with("<h1>Hi</h1>"));
+        assert!(
+            html.contains("<a href=\"https://capi.finance\" rel=\"noopener noreferrer\">site</a>")
+        );
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("script"));
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use std::{sync::Arc, time::Duration};
+
+use serde::Deserialize;
+use tokio::task::JoinHandle;
+
+use crate::{
+    chain::{
+        indexer::ChainIndexer,
+        tx_submitter::{PendingStatus, TxSubmitter},
+    },
+    dao::submitted_tx_dao::{StatusUpdate, SubmissionStatus, SubmittedTxDao},
+    error::ServiceError,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelayConfig {
+    /// How often the pending submitted transactions are checked
+    pub poll_interval_secs: u64,
+}
+
+/// Starts the task that periodically updates the status of the pending submitted transactions.
+pub fn start_tracking(
+    config: &RelayConfig,
+    submitter: Arc<dyn TxSubmitter>,
+    indexer: Arc<dyn ChainIndexer>,
+    dao: Arc<dyn SubmittedTxDao>,
+) -> JoinHandle<()> {
+    let poll_interval = Duration::from_secs(config.poll_interval_secs);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            interval.tick().await;
+            if let Err(e) = track_pending_txs(&*submitter, &*indexer, &*dao).await {
+                log::error!("Tracking submitted transactions failed: {}", e);
+            }
+        }
+    })
+}
+
+async fn track_pending_txs(
+    submitter: &dyn TxSubmitter,
+    indexer: &dyn ChainIndexer,
+    dao: &dyn SubmittedTxDao,
+) -> Result<(), ServiceError> {
+    let pending = dao.load_pending_txs().await?;
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let round = submitter.current_round().await?;
+    // a failing transaction doesn't prevent tracking the others
+    for tx in pending {
+        if let Err(e) = track_tx(submitter, indexer, dao, &tx.txid, round, tx.last_valid).await {
+            log::error!("Tracking submitted transaction failed: {}: {}", tx.txid, e);
+        }
+    }
+    Ok(())
+}
+
+async fn track_tx(
+    submitter: &dyn TxSubmitter,
+    indexer: &dyn ChainIndexer,
+    dao: &dyn SubmittedTxDao,
+    txid: &str,
+    round: u64,
+    last_valid: u64,
+) -> Result<(), ServiceError> {
+    let status = submitter.pending_status(txid).await?;
+    let mut update = status_update(status, round, last_valid);
+    if let Some(StatusUpdate {
+        status: SubmissionStatus::Expired,
+        ..
+    }) = update
+    {
+        update = indexed_status_update(indexer, txid, last_valid).await?;
+    }
+    if let Some(update) = update {
+        log::debug!("Submitted transaction: {}: {:?}", txid, update.status);
+        dao.update_status(txid, &update).await?;
+    }
+    Ok(())
+}
+
+/// None if the transaction is still pending
+fn status_update(status: PendingStatus, round: u64, last_valid: u64) -> Option<StatusUpdate> {
+    match status {
+        PendingStatus::Confirmed(confirmed_round) => Some(StatusUpdate {
+            status: SubmissionStatus::Confirmed,
+            confirmed_round: Some(confirmed_round),
+            error: None,
+        }),
+        PendingStatus::Rejected(error) => Some(StatusUpdate {
+            status: SubmissionStatus::Rejected,
+            confirmed_round: None,
+            error: Some(error),
+        }),
+        // can't be confirmed anymore, if it wasn't already (see indexed_status_update)
+        PendingStatus::InPool | PendingStatus::NotFound if round > last_valid => {
+            Some(StatusUpdate {
+                status: SubmissionStatus::Expired,
+                confirmed_round: None,
+                error: None,
+            })
+        }
+        PendingStatus::InPool | PendingStatus::NotFound => None,
+    }
+}
+
+/// Algod forgets confirmed transactions after a while (and after a restart): the indexer decides
+/// whether a transaction algod doesn't know anymore was confirmed or expired.
+/// None (still pending) while the indexer hasn't reached the last valid round.
+async fn indexed_status_update(
+    indexer: &dyn ChainIndexer,
+    txid: &str,
+    last_valid: u64,
+) -> Result<Option<StatusUpdate>, ServiceError> {
+    if let Some(tx) = indexer.transaction(txid).await? {
+        return Ok(Some(StatusUpdate {
+            status: SubmissionStatus::Confirmed,
+            confirmed_round: Some(tx.confirmed_round),
+            error: None,
+        }));
+    }
+    if indexer.current_round().await? < last_valid {
+        return Ok(None);
+    }
+    Ok(Some(StatusUpdate {
+        status: SubmissionStatus::Expired,
+        confirmed_round: None,
+        error: None,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{indexed_status_update, status_update};
+    use crate::{
+        chain::{
+            indexer::{AssetBalance, ChainIndexer, IndexerTransaction, TransactionQuery},
+            tx_submitter::PendingStatus,
+        },
+        dao::submitted_tx_dao::SubmissionStatus,
+        error::ServiceError,
+        test_data::{IndexerTxBuilder, INVESTOR},
+    };
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use tokio::test;
+
+    #[test]
+    async fn test_status_update() -> Result<()> {
+        let status = |pending, round| status_update(pending, round, 2000).map(|u| u.status);
+
+        assert_eq!(None, status(PendingStatus::InPool, 1500));
+        assert_eq!(None, status(PendingStatus::NotFound, 2000));
+        assert_eq!(
+            Some(SubmissionStatus::Confirmed),
+            status(PendingStatus::Confirmed(1501), 1600)
+        );
+        assert_eq!(
+            Some(SubmissionStatus::Rejected),
+            status(PendingStatus::Rejected("overspend".to_owned()), 1500)
+        );
+        assert_eq!(
+            Some(SubmissionStatus::Expired),
+            status(PendingStatus::InPool, 2001)
+        );
+        Ok(())
+    }
+
+    #[test]
+    async fn test_indexed_status_update() -> Result<()> {
+        let status = |indexer: StubIndexer| async move {
+            indexed_status_update(&indexer, "TX1", 2000)
+                .await
+                .map(|update| update.map(|u| (u.status, u.confirmed_round)))
+        };
+
+        // forgotten by algod, but confirmed
+        assert_eq!(
+            Some((SubmissionStatus::Confirmed, Some(1501))),
+            status(StubIndexer {
+                round: 2500,
+                confirmed_round: Some(1501)
+            })
+            .await?
+        );
+        // the indexer may not have indexed it yet
+        assert_eq!(
+            None,
+            status(StubIndexer {
+                round: 1900,
+                confirmed_round: None
+            })
+            .await?
+        );
+        assert_eq!(
+            Some((SubmissionStatus::Expired, None)),
+            status(StubIndexer {
+                round: 2001,
+                confirmed_round: None
+            })
+            .await?
+        );
+        Ok(())
+    }
+
+    /// Knows (only) the transaction TX1, if confirmed
+    struct StubIndexer {
+        round: u64,
+        confirmed_round: Option<u64>,
+    }
+
+    #[async_trait]
+    impl ChainIndexer for StubIndexer {
+        async fn current_round(&self) -> Result<u64, ServiceError> {
+            Ok(self.round)
+        }
+
+        async fn transactions(
+            &self,
+            _query: &TransactionQuery,
+        ) -> Result<Vec<IndexerTransaction>, ServiceError> {
+            Ok(vec![])
+        }
+
+        async fn app_calls(
+            &self,
+            _app_id: u64,
+            _min_round: u64,
+            _max_round: u64,
+        ) -> Result<Vec<IndexerTransaction>, ServiceError> {
+            Ok(vec![])
+        }
+
+        async fn transaction(
+            &self,
+            txid: &str,
+        ) -> Result<Option<IndexerTransaction>, ServiceError> {
+            Ok(self.confirmed_round.map(|round| {
+                IndexerTxBuilder::new(txid, INVESTOR)
+                    .confirmed_round(round)
+                    .build()
+            }))
+        }
+
+        async fn asset_amount(&self, _address: &str, _asset_id: u64) -> Result<u64, ServiceError> {
+            Ok(0)
+        }
+
+        async fn asset_balances(&self, _asset_id: u64) -> Result<Vec<AssetBalance>, ServiceError> {
+            Ok(vec![])
+        }
+    }
+}
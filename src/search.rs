@@ -0,0 +1,69 @@
+/// Markers used by the database to delimit the matches in highlights (see `ts_headline`).
+/// Control characters, so they can't collide with the text.
+pub const HIGHLIGHT_START: &str = "\u{2}";
+pub const HIGHLIGHT_STOP: &str = "\u{3}";
+
+/// Converts user input into a `to_tsquery` expression, matching all the words as prefixes ("cap sto" -> "cap:* & sto:*").
+/// Everything that isn't alphanumeric separates words, so tsquery operators in the input are ignored.
+/// None if the input has no words.
+pub fn to_prefix_tsquery(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("{}:*", word.to_lowercase()))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" & "))
+    }
+}
+
+/// Escapes the highlighted text and replaces the match markers with <mark> tags
+pub fn highlight_to_html(highlight: &str) -> String {
+    html_escape(highlight)
+        .replace(HIGHLIGHT_START, "<mark>")
+        .replace(HIGHLIGHT_STOP, "</mark>")
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::{highlight_to_html, to_prefix_tsquery, HIGHLIGHT_START, HIGHLIGHT_STOP};
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_prefix_tsquery_ignores_operators() -> Result<()> {
+        assert_eq!(
+            Some("cap:* & coffee:* & shop:*".to_owned()),
+            to_prefix_tsquery(" Cap & (coffee | !shop):*")
+        );
+        assert_eq!(None, to_prefix_tsquery(" &|! "));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_highlight_is_escaped() -> Result<()> {
+        let highlight = format!("<script> {}Coffee{} & co", HIGHLIGHT_START, HIGHLIGHT_STOP);
+        assert_eq!(
+            "&lt;script&gt; <mark>Coffee</mark> &amp; co",
+            highlight_to_html(&highlight)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+
+use deunicode::deunicode;
+
+pub const MAX_SLUG_LENGTH: usize = 48;
+
+/// Would clash with frontend routes
+const RESERVED_SLUGS: &[&str] = &[
+    "admin",
+    "api",
+    "auth",
+    "create",
+    "edit",
+    "explore",
+    "images",
+    "invest",
+    "investment",
+    "new",
+    "p",
+    "project",
+    "projects",
+    "search",
+    "settings",
+    "static",
+];
+
+/// Converts a project name into an url-safe slug: lowercase ascii letters and digits separated by "-".
+/// Other scripts and accents are transliterated first ("Café" -> "cafe", "Кофе" -> "kofe").
+/// The remaining characters are dropped or act as separators.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in deunicode(name).chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if (c.is_whitespace() || c.is_ascii_punctuation()) && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let mut slug: String = slug
+        .trim_matches('-')
+        .chars()
+        .take(MAX_SLUG_LENGTH)
+        .collect();
+    // truncating may leave a trailing separator
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    if slug.is_empty() {
+        "project".to_owned()
+    } else {
+        slug
+    }
+}
+
+/// Returns `base` or, if it's reserved or taken, the first free `base-<n>` (starting at 2)
+pub fn unique_slug(base: &str, taken: &HashSet<String>) -> String {
+    let is_free = |slug: &str| !RESERVED_SLUGS.contains(&slug) && !taken.contains(slug);
+    if is_free(base) {
+        return base.to_owned();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|slug| is_free(slug))
+        // the range is infinite and taken finite
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{slugify, unique_slug};
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_slugify() -> Result<()> {
+        assert_eq!("my-coffee-shop-2", slugify("  My Coffee-Shop #2! "));
+        assert_eq!("cafe-ole", slugify("Café Olé"));
+        assert_eq!("kofe", slugify("Кофе"));
+        assert_eq!("ka-fei", slugify("咖啡"));
+        assert_eq!("project", slugify("#!?"));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_unique_slug_skips_taken_and_reserved() -> Result<()> {
+        let taken: HashSet<String> = vec!["coffee".to_owned(), "coffee-2".to_owned()]
+            .into_iter()
+            .collect();
+        assert_eq!("coffee-3", unique_slug("coffee", &taken));
+        assert_eq!("search-2", unique_slug("search", &taken));
+        assert_eq!("tea", unique_slug("tea", &taken));
+        Ok(())
+    }
+}
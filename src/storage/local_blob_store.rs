@@ -0,0 +1,82 @@
+use std::{io::ErrorKind, path::PathBuf};
+
+use async_trait::async_trait;
+use tokio::fs;
+
+use crate::error::ServiceError;
+
+use super::{validate_key, BlobStore};
+
+/// Stores the blobs as files under `root`. The content type isn't stored.
+pub struct LocalBlobStore {
+    pub root: PathBuf,
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(
+        &self,
+        key: &str,
+        _content_type: &str,
+        bytes: Vec<u8>,
+    ) -> Result<(), ServiceError> {
+        validate_key(key)?;
+        let path = self.root.join(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await.map_err(io_error)?;
+        }
+        // write and rename, so readers never see partially written files
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes).await.map_err(io_error)?;
+        fs::rename(&tmp_path, &path).await.map_err(io_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ServiceError> {
+        validate_key(key)?;
+        match fs::read(self.root.join(key)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ServiceError> {
+        validate_key(key)?;
+        match fs::remove_file(self.root.join(key)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(io_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("Blob store IO error: {}", e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::LocalBlobStore;
+    use crate::{error::ServiceError, storage::BlobStore};
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_put_and_get() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("blobs-{}", uuid::Uuid::new_v4()));
+        let store = LocalBlobStore { root: root.clone() };
+
+        store.put("images/1", "image/png", vec![1, 2, 3]).await?;
+        assert_eq!(Some(vec![1, 2, 3]), store.get("images/1").await?);
+        assert_eq!(None, store.get("images/2").await?);
+
+        store.delete("images/1").await?;
+        assert_eq!(None, store.get("images/1").await?);
+        store.delete("images/1").await?;
+
+        let res = store.put("../outside", "image/png", vec![]).await;
+        assert!(matches!(res, Err(ServiceError::Internal(_))));
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::error::ServiceError;
+
+use self::{local_blob_store::LocalBlobStore, s3_blob_store::S3BlobStore};
+
+pub mod local_blob_store;
+pub mod s3_blob_store;
+
+/// Stores binary content (e.g. images) by key. Keys are paths like "images/<id>".
+#[async_trait]
+pub trait BlobStore: Sync + Send {
+    /// Overwrites existing content
+    async fn put(&self, key: &str, content_type: &str, bytes: Vec<u8>) -> Result<(), ServiceError>;
+    /// None if there's nothing stored with the key
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ServiceError>;
+    /// Ok if there's nothing stored with the key
+    async fn delete(&self, key: &str) -> Result<(), ServiceError>;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StorageConfig {
+    Local {
+        path: PathBuf,
+    },
+    /// S3 or compatible (e.g. MinIO, with `endpoint`)
+    S3 {
+        bucket: String,
+        region: String,
+        endpoint: Option<String>,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+pub fn create_blob_store(config: &StorageConfig) -> Result<Arc<dyn BlobStore>> {
+    Ok(match config {
+        StorageConfig::Local { path } => Arc::new(LocalBlobStore { root: path.clone() }),
+        StorageConfig::S3 {
+            bucket,
+            region,
+            endpoint,
+            access_key,
+            secret_key,
+        } => Arc::new(S3BlobStore::new(
+            bucket,
+            region,
+            endpoint.as_deref(),
+            access_key,
+            secret_key,
+        )?),
+    })
+}
+
+/// Keys are generated by us, but we don't want a bug to write outside of the store
+fn validate_key(key: &str) -> Result<(), ServiceError> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(ServiceError::Internal(format!("Invalid blob key: {}", key)))
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use s3::{bucket::Bucket, creds::Credentials, region::Region};
+
+use crate::error::ServiceError;
+
+use super::{validate_key, BlobStore};
+
+pub struct S3BlobStore {
+    bucket: Bucket,
+}
+
+impl S3BlobStore {
+    /// `endpoint`: for S3 compatible services. Uses path style urls (<endpoint>/<bucket>/<key>) in that case.
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        endpoint: Option<&str>,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Result<S3BlobStore> {
+        let credentials = Credentials::new(Some(access_key), Some(secret_key), None, None, None)?;
+        let bucket = match endpoint {
+            Some(endpoint) => Bucket::new_with_path_style(
+                bucket,
+                Region::Custom {
+                    region: region.to_owned(),
+                    endpoint: endpoint.to_owned(),
+                },
+                credentials,
+            )?,
+            None => Bucket::new(bucket, region.parse()?, credentials)?,
+        };
+        Ok(S3BlobStore { bucket })
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3BlobStore {
+    async fn put(&self, key: &str, content_type: &str, bytes: Vec<u8>) -> Result<(), ServiceError> {
+        validate_key(key)?;
+        let (_, status) = self
+            .bucket
+            .put_object_with_content_type(key, &bytes, content_type)
+            .await
+            .map_err(|e| ServiceError::Upstream(format!("S3 put failed: {}", e)))?;
+        match status {
+            200..=299 => Ok(()),
+            _ => Err(ServiceError::Upstream(format!(
+                "S3 put failed with status: {}",
+                status
+            ))),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, ServiceError> {
+        validate_key(key)?;
+        let (bytes, status) = self
+            .bucket
+            .get_object(key)
+            .await
+            .map_err(|e| ServiceError::Upstream(format!("S3 get failed: {}", e)))?;
+        match status {
+            200..=299 => Ok(Some(bytes)),
+            404 => Ok(None),
+            _ => Err(ServiceError::Upstream(format!(
+                "S3 get failed with status: {}",
+                status
+            ))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), ServiceError> {
+        validate_key(key)?;
+        let (_, status) = self
+            .bucket
+            .delete_object(key)
+            .await
+            .map_err(|e| ServiceError::Upstream(format!("S3 delete failed: {}", e)))?;
+        // S3 doesn't fail for missing objects (204), compatible services may (404)
+        match status {
+            200..=299 | 404 => Ok(()),
+            _ => Err(ServiceError::Upstream(format!(
+                "S3 delete failed with status: {}",
+                status
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use super::S3BlobStore;
+    use crate::storage::BlobStore;
+    use anyhow::Result;
+    use tokio::test;
+    use warp::{hyper::body::Bytes, path::Tail, Filter};
+
+    #[test]
+    async fn test_put_and_get_with_s3_compatible_service() -> Result<()> {
+        let store = store_with_stub_s3()?;
+
+        store.put("images/1", "image/png", vec![1, 2, 3]).await?;
+        assert_eq!(Some(vec![1, 2, 3]), store.get("images/1").await?);
+        assert_eq!(None, store.get("images/2").await?);
+
+        store.delete("images/1").await?;
+        assert_eq!(None, store.get("images/1").await?);
+        Ok(())
+    }
+
+    /// Starts an HTTP server storing objects in memory with the subset of the S3 API we use
+    /// (path style, requests aren't authenticated).
+    fn store_with_stub_s3() -> Result<S3BlobStore> {
+        let objects: Arc<Mutex<HashMap<String, Vec<u8>>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        let put_objects = objects.clone();
+        let put = warp::put()
+            .and(warp::path::tail())
+            .and(warp::body::bytes())
+            .map(move |path: Tail, body: Bytes| {
+                put_objects
+                    .lock()
+                    .unwrap()
+                    .insert(path.as_str().to_owned(), body.to_vec());
+                warp::reply()
+            });
+        let delete_objects = objects.clone();
+        let delete = warp::delete()
+            .and(warp::path::tail())
+            .map(move |path: Tail| {
+                delete_objects.lock().unwrap().remove(path.as_str());
+                warp::reply::with_status(warp::reply(), warp::http::StatusCode::NO_CONTENT)
+            });
+        let get = warp::get().and(warp::path::tail()).map(move |path: Tail| {
+            match objects.lock().unwrap().get(path.as_str()) {
+                Some(bytes) => warp::reply::with_status(bytes.clone(), warp::http::StatusCode::OK),
+                None => warp::reply::with_status(vec![], warp::http::StatusCode::NOT_FOUND),
+            }
+        });
+
+        let (addr, server) =
+            warp::serve(put.or(delete).or(get)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        S3BlobStore::new(
+            "capi",
+            "us-east-1",
+            Some(&format!("http://{}", addr)),
+            "access",
+            "secret",
+        )
+    }
+}
@@ -0,0 +1,374 @@
+use std::{collections::BTreeSet, convert::TryFrom};
+
+use algonaut::core::Address;
+use anyhow::{anyhow, Result};
+use data_encoding::HEXLOWER;
+
+use self::fields::{ASSET_HOLDING_FIELDS, ASSET_PARAMS_FIELDS, GLOBAL_FIELDS, TXN_FIELDS};
+
+mod fields;
+
+/// A disassembled TEAL program, in the format of `goal clerk compile -D`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    pub version: u64,
+    /// Starting with the version pragma. Branch targets are labeled.
+    pub lines: Vec<String>,
+}
+
+/// Supports the opcodes of TEAL v1 - v4, fails on others (e.g. inner transactions).
+pub fn disassemble(program: &[u8]) -> Result<Disassembly> {
+    let mut reader = Reader { program, pc: 0 };
+    let version = reader.varuint()?;
+
+    let mut instructions = vec![];
+    while !reader.is_at_end() {
+        let pc = reader.pc;
+        instructions.push((pc, read_instruction(&mut reader)?));
+    }
+
+    let targets: BTreeSet<usize> = instructions
+        .iter()
+        .flat_map(|(_, instruction)| instruction.target)
+        .collect();
+    let label = |target: usize| {
+        // the targets are in the set
+        let index = targets.iter().position(|t| *t == target).unwrap_or(0);
+        format!("label{}", index + 1)
+    };
+
+    let mut lines = vec![format!("#pragma version {}", version)];
+    for (pc, instruction) in &instructions {
+        if targets.contains(pc) {
+            lines.push(format!("{}:", label(*pc)));
+        }
+        let mut line = instruction.text.clone();
+        if let Some(target) = instruction.target {
+            line = format!("{} {}", line, label(target));
+        }
+        lines.push(line);
+    }
+    // a branch can jump to the end of the program (exits with the top of the stack)
+    if targets.contains(&program.len()) {
+        lines.push(format!("{}:", label(program.len())));
+    }
+
+    Ok(Disassembly { version, lines })
+}
+
+struct Instruction {
+    /// The opcode and its immediate arguments, except the branch target
+    text: String,
+    /// Branch target (pc)
+    target: Option<usize>,
+}
+
+impl Instruction {
+    fn new(text: String) -> Instruction {
+        Instruction { text, target: None }
+    }
+}
+
+fn read_instruction(reader: &mut Reader) -> Result<Instruction> {
+    let pc = reader.pc;
+    let opcode = reader.byte()?;
+
+    if let Some(name) = simple_op(opcode) {
+        return Ok(Instruction::new(name.to_owned()));
+    }
+
+    let text = match opcode {
+        0x20 => {
+            let count = reader.varuint()?;
+            let mut text = "intcblock".to_owned();
+            for _ in 0..count {
+                text = format!("{} {}", text, reader.varuint()?);
+            }
+            text
+        }
+        0x26 => {
+            let count = reader.varuint()?;
+            let mut text = "bytecblock".to_owned();
+            for _ in 0..count {
+                let len = usize::try_from(reader.varuint()?)?;
+                text = format!("{} 0x{}", text, HEXLOWER.encode(reader.bytes(len)?));
+            }
+            text
+        }
+        0x21 => format!("intc {}", reader.byte()?),
+        0x27 => format!("bytec {}", reader.byte()?),
+        0x2c => format!("arg {}", reader.byte()?),
+        0x31 => format!("txn {}", field(TXN_FIELDS, reader.byte()?)?),
+        0x32 => format!("global {}", field(GLOBAL_FIELDS, reader.byte()?)?),
+        0x33 => {
+            let index = reader.byte()?;
+            format!("gtxn {} {}", index, field(TXN_FIELDS, reader.byte()?)?)
+        }
+        0x34 => format!("load {}", reader.byte()?),
+        0x35 => format!("store {}", reader.byte()?),
+        0x36 => {
+            let name = field(TXN_FIELDS, reader.byte()?)?;
+            format!("txna {} {}", name, reader.byte()?)
+        }
+        0x37 => {
+            let index = reader.byte()?;
+            let name = field(TXN_FIELDS, reader.byte()?)?;
+            format!("gtxna {} {} {}", index, name, reader.byte()?)
+        }
+        0x38 => format!("gtxns {}", field(TXN_FIELDS, reader.byte()?)?),
+        0x39 => {
+            let name = field(TXN_FIELDS, reader.byte()?)?;
+            format!("gtxnsa {} {}", name, reader.byte()?)
+        }
+        0x3a => {
+            let index = reader.byte()?;
+            format!("gload {} {}", index, reader.byte()?)
+        }
+        0x3b => format!("gloads {}", reader.byte()?),
+        0x3c => format!("gaid {}", reader.byte()?),
+        0x40 | 0x41 | 0x42 | 0x88 => {
+            let name = match opcode {
+                0x40 => "bnz",
+                0x41 => "bz",
+                0x42 => "b",
+                _ => "callsub",
+            };
+            let offset = reader.i16()?;
+            let target = reader.pc as i64 + i64::from(offset);
+            if target < 0 || target as usize > reader.program.len() {
+                return Err(anyhow!("Branch out of the program at: {}", pc));
+            }
+            return Ok(Instruction {
+                text: name.to_owned(),
+                target: Some(target as usize),
+            });
+        }
+        0x4b => format!("dig {}", reader.byte()?),
+        0x51 => {
+            let start = reader.byte()?;
+            format!("substring {} {}", start, reader.byte()?)
+        }
+        0x70 => format!(
+            "asset_holding_get {}",
+            field(ASSET_HOLDING_FIELDS, reader.byte()?)?
+        ),
+        0x71 => format!(
+            "asset_params_get {}",
+            field(ASSET_PARAMS_FIELDS, reader.byte()?)?
+        ),
+        0x80 => {
+            let len = usize::try_from(reader.varuint()?)?;
+            let bytes = reader.bytes(len)?;
+            let text = format!("pushbytes 0x{}", HEXLOWER.encode(bytes));
+            // most likely an account the program checks
+            match <[u8; 32]>::try_from(bytes) {
+                Ok(address) => format!("{} // addr {}", text, Address::new(address)),
+                Err(_) => text,
+            }
+        }
+        0x81 => format!("pushint {}", reader.varuint()?),
+        _ => return Err(anyhow!("Unsupported opcode: 0x{:02x} at: {}", opcode, pc)),
+    };
+    Ok(Instruction::new(text))
+}
+
+/// The opcodes without immediate arguments
+fn simple_op(opcode: u8) -> Option<&'static str> {
+    Some(match opcode {
+        0x00 => "err",
+        0x01 => "sha256",
+        0x02 => "keccak256",
+        0x03 => "sha512_256",
+        0x04 => "ed25519verify",
+        0x08 => "+",
+        0x09 => "-",
+        0x0a => "/",
+        0x0b => "*",
+        0x0c => "<",
+        0x0d => ">",
+        0x0e => "<=",
+        0x0f => ">=",
+        0x10 => "&&",
+        0x11 => "||",
+        0x12 => "==",
+        0x13 => "!=",
+        0x14 => "!",
+        0x15 => "len",
+        0x16 => "itob",
+        0x17 => "btoi",
+        0x18 => "%",
+        0x19 => "|",
+        0x1a => "&",
+        0x1b => "^",
+        0x1c => "~",
+        0x1d => "mulw",
+        0x1e => "addw",
+        0x1f => "divmodw",
+        0x22 => "intc_0",
+        0x23 => "intc_1",
+        0x24 => "intc_2",
+        0x25 => "intc_3",
+        0x28 => "bytec_0",
+        0x29 => "bytec_1",
+        0x2a => "bytec_2",
+        0x2b => "bytec_3",
+        0x2d => "arg_0",
+        0x2e => "arg_1",
+        0x2f => "arg_2",
+        0x30 => "arg_3",
+        0x3d => "gaids",
+        0x43 => "return",
+        0x44 => "assert",
+        0x48 => "pop",
+        0x49 => "dup",
+        0x4a => "dup2",
+        0x4c => "swap",
+        0x4d => "select",
+        0x50 => "concat",
+        0x52 => "substring3",
+        0x53 => "getbit",
+        0x54 => "setbit",
+        0x55 => "getbyte",
+        0x56 => "setbyte",
+        0x60 => "balance",
+        0x61 => "app_opted_in",
+        0x62 => "app_local_get",
+        0x63 => "app_local_get_ex",
+        0x64 => "app_global_get",
+        0x65 => "app_global_get_ex",
+        0x66 => "app_local_put",
+        0x67 => "app_global_put",
+        0x68 => "app_local_del",
+        0x69 => "app_global_del",
+        0x78 => "min_balance",
+        0x89 => "retsub",
+        0x90 => "shl",
+        0x91 => "shr",
+        0x92 => "sqrt",
+        0x93 => "bitlen",
+        0x94 => "exp",
+        0x95 => "expw",
+        0xa0 => "b+",
+        0xa1 => "b-",
+        0xa2 => "b/",
+        0xa3 => "b*",
+        0xa4 => "b<",
+        0xa5 => "b>",
+        0xa6 => "b<=",
+        0xa7 => "b>=",
+        0xa8 => "b==",
+        0xa9 => "b!=",
+        0xaa => "b%",
+        0xab => "b|",
+        0xac => "b&",
+        0xad => "b^",
+        0xae => "b~",
+        0xaf => "bzero",
+        _ => return None,
+    })
+}
+
+fn field(names: &[&'static str], index: u8) -> Result<&'static str> {
+    names
+        .get(usize::from(index))
+        .copied()
+        .ok_or_else(|| anyhow!("Unknown field: {}", index))
+}
+
+struct Reader<'a> {
+    program: &'a [u8],
+    pc: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_at_end(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pc
+            .checked_add(len)
+            .filter(|end| *end <= self.program.len())
+            .ok_or_else(|| anyhow!("Unexpected end of the program at: {}", self.pc))?;
+        let bytes = &self.program[self.pc..end];
+        self.pc = end;
+        Ok(bytes)
+    }
+
+    /// Big endian
+    fn i16(&mut self) -> Result<i16> {
+        let bytes = self.bytes(2)?;
+        Ok(i16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Unsigned LEB128
+    fn varuint(&mut self) -> Result<u64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("Invalid varuint at: {}", self.pc))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryInto;
+
+    use super::disassemble;
+    use crate::test_data::project_json;
+    use anyhow::{Error, Result};
+    use core_::flows::create_project::model::Project;
+    use tokio::test;
+
+    #[test]
+    async fn test_disassemble_customer_escrow() -> Result<()> {
+        let project: Project = project_json()?.try_into().map_err(Error::msg)?;
+
+        let disassembly = disassemble(&project.customer_escrow.program.0)?;
+
+        assert_eq!(4, disassembly.version);
+        assert_eq!(
+            vec![
+                "#pragma version 4",
+                "intcblock 1",
+                "global GroupSize",
+                "pushint 3",
+                "==",
+                "bnz label1",
+                "pushint 0",
+                "return",
+                "label1:",
+                "gtxn 0 TypeEnum",
+                "pushint 6",
+                "==",
+            ],
+            disassembly.lines[..12].to_vec()
+        );
+        // the customer escrow forwards the payments to the central escrow
+        let central_escrow = project.central_escrow.address().to_string();
+        assert!(disassembly
+            .lines
+            .iter()
+            .any(|line| line.ends_with(&format!("// addr {}", central_escrow))));
+        Ok(())
+    }
+
+    #[test]
+    async fn test_refuses_truncated_program() -> Result<()> {
+        // pushint without its argument
+        assert!(disassemble(&[4, 0x81]).is_err());
+        // branch beyond the end
+        assert!(disassemble(&[4, 0x42, 0, 9]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use algonaut::algod::v2::Algod;
+use anyhow::Result;
+use core_::api::json_workaround::ProjectJson;
+use serde_json::json;
+use warp::Filter;
+
+use crate::chain::indexer::{AssetTransferTransaction, IndexerTransaction, PaymentTransaction};
+
+/// The creator of project_json
+pub const CREATOR: &str = "MKRBTLNZRS3UZZDS5OWPLP7YPHUDNKXFUFN5PNCJ3P2XRG74HNOGY6XOYQ";
+pub const INVESTOR: &str = "34RAC265MPZYSWMTGHZWJPCQMSYYOS243VWBTOSS6T5LOZRHJ4XPJ6Z3BI";
+pub const CUSTOMER: &str = "W3CFQY4HLY2EQ7FDYFK62FC67YJKORMB4J5673C2UZQ3R3UMU3OSFVZXVI";
+
+// generated with client app - convenience to test quickly, should be replaced with regular mock data
+pub fn project_json() -> Result<ProjectJson> {
+    let json = r#"{"specs":{"name":"my1project","shares":{"token_name":"foo","count":100},"investors_share":40,"asset_price":1000000},"creator_address":"MKRBTLNZRS3UZZDS5OWPLP7YPHUDNKXFUFN5PNCJ3P2XRG74HNOGY6XOYQ","shares_asset_id":42,"central_app_id":50,"invest_escrow":{"address":"SV2LIUFR5AL2BZOMGW3SAYU5FT2T662NOXPVKXF3GKGTDYRZJMHENNZS2Y","program":[4,32,6,6,42,0,232,7,43,4,50,4,34,18,51,2,17,35,18,16,51,3,17,33,4,18,16,64,0,9,50,4,34,18,64,0,83,36,67,51,2,17,35,18,51,2,16,33,5,18,16,51,2,18,36,18,16,51,2,1,37,14,16,51,2,32,50,3,18,16,51,2,21,50,3,18,16,51,3,17,33,4,18,16,51,3,16,33,5,18,16,51,3,18,36,18,16,51,3,1,37,14,16,51,3,32,50,3,18,16,51,3,21,50,3,18,16,66,0,91,51,0,16,34,18,51,3,17,35,18,16,51,3,20,128,32,247,10,15,104,164,223,249,27,116,139,66,224,167,91,33,215,215,35,34,187,44,221,159,36,227,39,167,77,162,152,169,0,18,16,51,3,1,37,14,16,51,3,21,50,3,18,16,51,3,32,50,3,18,16,51,1,8,51,3,18,129,192,132,61,11,18,16,51,3,18,51,4,18,18,16]},"staking_escrow":{"address":"64FA62FE374RW5ELILQKOWZB27LSGIV3FTOZ6JHDE6TU3IUYVEAKZXC3DQ","program":[4,32,6,4,6,0,42,43,232,7,50,4,35,18,51,0,17,37,18,16,51,1,17,33,4,18,16,64,0,18,50,4,129,2,18,64,0,89,50,4,129,3,18,64,0,93,36,67,51,0,17,37,18,51,0,16,34,18,16,51,0,18,36,18,16,51,0,1,33,5,14,16,51,0,32,50,3,18,16,51,0,21,50,3,18,16,51,1,17,33,4,18,16,51,1,16,34,18,16,51,1,18,36,18,16,51,1,1,33,5,14,16,51,1,32,50,3,18,16,51,1,21,50,3,18,16,67,51,0,16,35,18,51,1,16,34,18,16,67,51,0,16,35,18,51,1,16,34,18,16,51,2,16,129,1,18,16]},"central_escrow":{"address":"P7GEWDXXW5IONRW6XRIRVPJCT2XXEQGOBGG65VJPBUOYZEJCBZWTPHS3VQ","program":[4,129,1]},"customer_escrow":{"address":"3BW2V2NE7AIFGSARHF7ULZFWJPCOYOJTP3NL6ZQ3TWMSK673HTWTPPKEBA","program":[4,32,1,1,50,4,129,3,18,64,0,3,129,0,67,51,0,16,129,6,18,51,1,16,34,18,16,51,1,1,129,232,7,14,16,51,1,32,50,3,18,16,51,1,21,50,3,18,16,51,1,7,128,32,127,204,75,14,247,183,80,230,198,222,188,81,26,189,34,158,175,114,64,206,9,141,238,213,47,13,29,140,145,34,14,109,18,16,51,2,16,34,18,16]},"uuid":"f5c8614f-f969-4e65-8039-15048a5055dd"}"#;
+    Ok(serde_json::from_str::<ProjectJson>(json)?)
+}
+
+/// Starts an HTTP server serving algod's suggested params (last round 1000, min fee 1000),
+/// for the transactions built with them. To be called from a test (needs the runtime).
+pub fn stub_algod() -> Result<Algod> {
+    let params = warp::path!("v2" / "transactions" / "params").map(|| {
+        warp::reply::json(&json!({
+            "consensus-version": "https://github.com/algorandfoundation/specs/tree/bc36005dbd776e6d1eaf0c560619bb183215645c",
+            "fee": 0,
+            "genesis-hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+            "genesis-id": "testnet-v1.0",
+            "last-round": 1000,
+            "min-fee": 1000
+        }))
+    });
+    let (addr, server) = warp::serve(params).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    Ok(Algod::new(
+        &format!("http://{}", addr),
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+    )?)
+}
+
+/// Builds the transactions returned by the indexer, confirmed in round 100 unless set.
+pub struct IndexerTxBuilder {
+    tx: IndexerTransaction,
+}
+
+impl IndexerTxBuilder {
+    pub fn new(id: &str, sender: &str) -> IndexerTxBuilder {
+        IndexerTxBuilder {
+            tx: IndexerTransaction {
+                id: id.to_owned(),
+                group: None,
+                confirmed_round: 100,
+                round_time: 1640995200,
+                sender: sender.to_owned(),
+                note: None,
+                payment_transaction: None,
+                asset_transfer_transaction: None,
+            },
+        }
+    }
+
+    pub fn group(mut self, group: Option<&str>) -> IndexerTxBuilder {
+        self.tx.group = group.map(str::to_owned);
+        self
+    }
+
+    pub fn confirmed_round(mut self, round: u64) -> IndexerTxBuilder {
+        self.tx.confirmed_round = round;
+        self
+    }
+
+    /// Base64
+    pub fn note(mut self, note: Option<&str>) -> IndexerTxBuilder {
+        self.tx.note = note.map(str::to_owned);
+        self
+    }
+
+    pub fn payment(mut self, receiver: &str, amount: u64) -> IndexerTxBuilder {
+        self.tx.payment_transaction = Some(PaymentTransaction {
+            receiver: receiver.to_owned(),
+            amount,
+        });
+        self
+    }
+
+    pub fn asset_transfer(mut self, receiver: &str, amount: u64) -> IndexerTxBuilder {
+        self.tx.asset_transfer_transaction = Some(AssetTransferTransaction {
+            receiver: receiver.to_owned(),
+            amount,
+        });
+        self
+    }
+
+    pub fn build(self) -> IndexerTransaction {
+        self.tx
+    }
+}
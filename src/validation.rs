@@ -0,0 +1,305 @@
+use std::convert::TryInto;
+
+use algonaut::{
+    core::{Address, CompiledTeal},
+    transaction::contract_account::ContractAccount,
+};
+use core_::{api::json_workaround::ProjectJson, flows::create_project::model::Project};
+use serde::Serialize;
+use url::Url;
+
+use crate::{dao::project_metadata_dao::ProjectMetadata, error::ServiceError};
+
+pub const MAX_PROJECT_NAME_LENGTH: usize = 64;
+// Algorand's limit for asset unit names (bytes)
+pub const MAX_TOKEN_NAME_LENGTH: usize = 8;
+pub const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+pub const MAX_URL_LENGTH: usize = 2048;
+pub const MAX_SOCIAL_LINKS: usize = 10;
+pub const MAX_WITHDRAWAL_DESCRIPTION_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> FieldError {
+        FieldError {
+            field: field.to_owned(),
+            message: message.to_owned(),
+        }
+    }
+}
+
+/// Validates a project submitted by a client and converts it into a project.
+/// Returns all the invalid fields, not only the first one.
+pub fn validate_project(project: ProjectJson) -> Result<Project, ServiceError> {
+    let mut errors = vec![];
+
+    validate_project_name("specs.name", &project.specs.name, &mut errors);
+
+    let token_name = project.specs.shares.token_name.trim();
+    if token_name.is_empty() {
+        errors.push(FieldError::new("specs.shares.token_name", "Required"));
+    } else if token_name.len() > MAX_TOKEN_NAME_LENGTH {
+        errors.push(FieldError::new(
+            "specs.shares.token_name",
+            &format!("Must be at most {} bytes", MAX_TOKEN_NAME_LENGTH),
+        ));
+    }
+
+    if project.specs.shares.count == 0 {
+        errors.push(FieldError::new(
+            "specs.shares.count",
+            "Must be greater than 0",
+        ));
+    }
+    if project.specs.asset_price.0 == 0 {
+        errors.push(FieldError::new(
+            "specs.asset_price",
+            "Must be greater than 0",
+        ));
+    }
+    if project.specs.investors_share > 100 {
+        errors.push(FieldError::new(
+            "specs.investors_share",
+            "Must be between 0 and 100",
+        ));
+    }
+
+    if project.creator_address.parse::<Address>().is_err() {
+        errors.push(FieldError::new("creator_address", "Invalid address"));
+    }
+    if project.shares_asset_id == 0 {
+        errors.push(FieldError::new("shares_asset_id", "Required"));
+    }
+    if project.central_app_id == 0 {
+        errors.push(FieldError::new("central_app_id", "Required"));
+    }
+
+    validate_escrow(
+        "invest_escrow",
+        &project.invest_escrow.address,
+        &project.invest_escrow.program,
+        &mut errors,
+    );
+    validate_escrow(
+        "staking_escrow",
+        &project.staking_escrow.address,
+        &project.staking_escrow.program,
+        &mut errors,
+    );
+    validate_escrow(
+        "central_escrow",
+        &project.central_escrow.address,
+        &project.central_escrow.program,
+        &mut errors,
+    );
+    validate_escrow(
+        "customer_escrow",
+        &project.customer_escrow.address,
+        &project.customer_escrow.program,
+        &mut errors,
+    );
+
+    if !errors.is_empty() {
+        return Err(ServiceError::Validation(errors));
+    }
+
+    project
+        .try_into()
+        .map_err(|e| ServiceError::InvalidInput(e.to_string()))
+}
+
+pub fn validate_project_name(field: &str, name: &str, errors: &mut Vec<FieldError>) {
+    let name = name.trim();
+    if name.is_empty() {
+        errors.push(FieldError::new(field, "Required"));
+    } else if name.chars().count() > MAX_PROJECT_NAME_LENGTH {
+        errors.push(FieldError::new(
+            field,
+            &format!("Must be at most {} characters", MAX_PROJECT_NAME_LENGTH),
+        ));
+    }
+}
+
+/// Validates the metadata submitted by a client, trimming the fields and turning empty urls into None.
+pub fn validate_metadata(metadata: ProjectMetadata) -> Result<ProjectMetadata, ServiceError> {
+    let mut errors = vec![];
+
+    let description = metadata.description.trim().to_owned();
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        errors.push(FieldError::new(
+            "description",
+            &format!("Must be at most {} characters", MAX_DESCRIPTION_LENGTH),
+        ));
+    }
+
+    let logo_url = validate_optional_url("logo_url", metadata.logo_url, &mut errors);
+    let cover_url = validate_optional_url("cover_url", metadata.cover_url, &mut errors);
+    let website_url = validate_optional_url("website_url", metadata.website_url, &mut errors);
+
+    if metadata.social_links.len() > MAX_SOCIAL_LINKS {
+        errors.push(FieldError::new(
+            "social_links",
+            &format!("At most {} links", MAX_SOCIAL_LINKS),
+        ));
+    }
+    let social_links = metadata
+        .social_links
+        .iter()
+        .enumerate()
+        .map(|(index, link)| {
+            let link = link.trim();
+            validate_url(&format!("social_links.{}", index), link, &mut errors);
+            link.to_owned()
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(ServiceError::Validation(errors));
+    }
+
+    Ok(ProjectMetadata {
+        description,
+        logo_url,
+        cover_url,
+        website_url,
+        social_links,
+        category: metadata.category,
+        logo_image_id: metadata.logo_image_id,
+        cover_image_id: metadata.cover_image_id,
+    })
+}
+
+/// Returns the trimmed description
+pub fn validate_withdrawal_description(description: &str) -> Result<String, ServiceError> {
+    let description = description.trim();
+    let error = if description.is_empty() {
+        "Required".to_owned()
+    } else if description.chars().count() > MAX_WITHDRAWAL_DESCRIPTION_LENGTH {
+        format!(
+            "Must be at most {} characters",
+            MAX_WITHDRAWAL_DESCRIPTION_LENGTH
+        )
+    } else {
+        return Ok(description.to_owned());
+    };
+    Err(ServiceError::Validation(vec![FieldError::new(
+        "description",
+        &error,
+    )]))
+}
+
+fn validate_optional_url(
+    field: &str,
+    url: Option<String>,
+    errors: &mut Vec<FieldError>,
+) -> Option<String> {
+    let url = url.map(|u| u.trim().to_owned()).filter(|u| !u.is_empty())?;
+    validate_url(field, &url, errors);
+    Some(url)
+}
+
+/// Only absolute http(s) urls: they're rendered as links or images
+fn validate_url(field: &str, url: &str, errors: &mut Vec<FieldError>) {
+    if url.len() > MAX_URL_LENGTH {
+        errors.push(FieldError::new(
+            field,
+            &format!("Must be at most {} characters", MAX_URL_LENGTH),
+        ));
+        return;
+    }
+    match Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => {}
+        Ok(_) => errors.push(FieldError::new(field, "Must be an http(s) url")),
+        Err(_) => errors.push(FieldError::new(field, "Invalid url")),
+    }
+}
+
+/// The escrow address has to be the hash of its program
+fn validate_escrow(field: &str, address: &str, program: &[u8], errors: &mut Vec<FieldError>) {
+    let address_field = format!("{}.address", field);
+    if program.is_empty() {
+        errors.push(FieldError::new(&format!("{}.program", field), "Required"));
+        return;
+    }
+    match address.parse::<Address>() {
+        Ok(address) => {
+            let contract_account = ContractAccount::new(CompiledTeal(program.to_vec()));
+            if *contract_account.address() != address {
+                errors.push(FieldError::new(
+                    &address_field,
+                    "Doesn't correspond to the program",
+                ));
+            }
+        }
+        Err(_) => errors.push(FieldError::new(&address_field, "Invalid address")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate_metadata, validate_project};
+    use crate::{
+        dao::project_metadata_dao::ProjectMetadata, error::ServiceError, test_data::project_json,
+    };
+    use anyhow::Result;
+    use tokio::test;
+
+    #[test]
+    async fn test_valid_project() -> Result<()> {
+        let project = validate_project(project_json()?)?;
+        assert_eq!("my1project", project.specs.name);
+        Ok(())
+    }
+
+    #[test]
+    async fn test_invalid_project_reports_all_fields() -> Result<()> {
+        let mut json = project_json()?;
+        json.specs.investors_share = 101;
+        json.specs.shares.token_name = "too_long_name".to_owned();
+        json.creator_address = "invalid".to_owned();
+        // program of another escrow
+        json.invest_escrow.program = json.central_escrow.program.clone();
+
+        match validate_project(json) {
+            Err(ServiceError::Validation(errors)) => {
+                let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(
+                    vec![
+                        "specs.shares.token_name",
+                        "specs.investors_share",
+                        "creator_address",
+                        "invest_escrow.address"
+                    ],
+                    fields
+                );
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        Ok(())
+    }
+
+    #[test]
+    async fn test_invalid_metadata_urls() -> Result<()> {
+        let metadata = ProjectMetadata {
+            description: " **Coffee** ".to_owned(),
+            logo_url: Some(" ".to_owned()),
+            website_url: Some("javascript:alert(1)".to_owned()),
+            social_links: vec!["https://twitter.com/capi".to_owned(), "capi".to_owned()],
+            ..ProjectMetadata::default()
+        };
+
+        match validate_metadata(metadata) {
+            Err(ServiceError::Validation(errors)) => {
+                let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+                assert_eq!(vec!["website_url", "social_links.1"], fields);
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        Ok(())
+    }
+}